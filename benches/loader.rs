@@ -0,0 +1,97 @@
+#![allow(dead_code, unused_imports)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+#[path = "../src/okroshka/mod.rs"]
+pub mod okroshka;
+
+use okroshka::ir::module::IRModule;
+
+const FUNCTIONS: usize = 2000;
+const INSTRUCTIONS: usize = 200;
+const DATA: usize = 2000;
+const DATA_ELEMENTS: usize = 64;
+const ITERATIONS: u32 = 5;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn large_module() -> String {
+    let mut json = String::new();
+    json.push_str(r#"{"globals": [], "externals": [], "string_literals": [], "inline_assembly": [],"#);
+    json.push_str(r#""types": [{"identifier": 0, "type": []}, {"identifier": 1, "type": [{"type": "array", "length": 512, "element_type": {"type": "char"}}]}],"#);
+
+    json.push_str(r#""function_declarations": ["#);
+    for index in 0..FUNCTIONS {
+        if index > 0 {
+            json.push(',');
+        }
+        write!(json, r#"{{"identifier": {}, "name": "func{}", "parameters": 0, "vararg": false, "returns": 0}}"#, index, index).unwrap();
+    }
+
+    json.push_str(r#"], "functions": ["#);
+    for index in 0..FUNCTIONS {
+        if index > 0 {
+            json.push(',');
+        }
+        write!(json, r#"{{"identifier": {}, "name": "func{}", "locals": 0, "body": ["#, index, index).unwrap();
+        for instr in 0..INSTRUCTIONS / 2 {
+            write!(json, r#"{{"opcode": "push", "arg": {}}}, {{"opcode": "pop"}},"#, instr).unwrap();
+        }
+        json.push_str(r#"{"opcode": "ret"}]}"#);
+    }
+
+    json.push_str(r#"], "data": ["#);
+    for index in 0..DATA {
+        if index > 0 {
+            json.push(',');
+        }
+        write!(json, r#"{{"identifier": "data{}", "storage": "global", "type": 1, "value": ["#, index).unwrap();
+        for element in 0..DATA_ELEMENTS {
+            if element > 0 {
+                json.push(',');
+            }
+            write!(json, r#"{{"class": "integer", "value": {}}}"#, element).unwrap();
+        }
+        json.push_str("]}");
+    }
+    json.push_str("]}");
+    json
+}
+
+fn main() {
+    let json = large_module();
+    let module = serde_json::from_str::<IRModule>(&json).unwrap();
+    assert_eq!(module.functions().count(), FUNCTIONS);
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        serde_json::from_str::<IRModule>(&json).unwrap();
+    }
+    println!("load {} bytes: {:?} and {} allocations per iteration", json.len(), start.elapsed() / ITERATIONS,
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS as usize);
+}
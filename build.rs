@@ -5,9 +5,8 @@ use std::io;
 use std::io::Write;
 use std::path::Path;
 use serde::Deserialize;
-use quick_xml;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 enum OpcodeClass {
     #[serde(rename = "none")]
     None,
@@ -37,6 +36,20 @@ enum OpcodeClass {
     MemFlags
 }
 
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+enum OpcodeControl {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "branch")]
+    Branch,
+    #[serde(rename = "jump")]
+    Jump,
+    #[serde(rename = "terminator")]
+    Terminator,
+    #[serde(rename = "call")]
+    Call
+}
+
 #[derive(Debug, Deserialize)]
 struct Opcode {
     #[serde(rename = "id")]
@@ -44,7 +57,158 @@ struct Opcode {
     mnemonic: String,
     code: String,
     #[serde(rename = "type")]
-    klass: OpcodeClass
+    klass: OpcodeClass,
+    control: Option<OpcodeControl>,
+    branch_target: Option<bool>,
+    inline_asm: Option<bool>,
+    constant_push: Option<bool>,
+    fold_arity: Option<usize>
+}
+
+impl OpcodeClass {
+    fn fields(&self) -> &'static str {
+        match self {
+            OpcodeClass::None => "",
+            OpcodeClass::Integer64 => "(i64)",
+            OpcodeClass::UInteger64 | OpcodeClass::String => "(u64)",
+            OpcodeClass::CodeReference => "(usize)",
+            OpcodeClass::Identifier => "(String)",
+            OpcodeClass::FunctionReference => "(u64, Option<String>)",
+            OpcodeClass::UInteger32 => "(u32, u32)",
+            OpcodeClass::TypeReference => "(IRTypeRef)",
+            OpcodeClass::Float32 => "(f32)",
+            OpcodeClass::Float64 => "(f64)",
+            OpcodeClass::Boolean => "(bool)",
+            OpcodeClass::MemFlags => "(IRInstructionMemFlags)"
+        }
+    }
+
+    fn owned_argument(&self) -> (&'static str, &'static str) {
+        match self {
+            OpcodeClass::None => ("None", ""),
+            OpcodeClass::Integer64 => ("Integer(x)", "(x)"),
+            OpcodeClass::UInteger64 => ("UInteger(x)", "(x)"),
+            OpcodeClass::UInteger32 => ("UIntegerPair(x, y)", "(x, y)"),
+            OpcodeClass::Float64 => ("Float64(x)", "(x)"),
+            OpcodeClass::Float32 => ("Float32(x)", "(x)"),
+            OpcodeClass::Boolean => ("Boolean(x)", "(x)"),
+            OpcodeClass::String => ("String(x)", "(x)"),
+            OpcodeClass::TypeReference => ("TypeRef(x)", "(x)"),
+            OpcodeClass::CodeReference => ("CodeRef(x)", "(x)"),
+            OpcodeClass::Identifier => ("Identifier(x)", "(x)"),
+            OpcodeClass::FunctionReference => ("FunctionRef(x, y)", "(x, y)"),
+            OpcodeClass::MemFlags => ("MemFlags(x)", "(x)")
+        }
+    }
+}
+
+impl Opcode {
+    fn control(&self) -> OpcodeControl {
+        match self.control {
+            Some(control) => control,
+            None => match self.identifier.as_str() {
+                "branch" => OpcodeControl::Branch,
+                "jmp" => OpcodeControl::Jump,
+                "ijmp" | "ret" => OpcodeControl::Terminator,
+                "invoke" | "invokev" => OpcodeControl::Call,
+                _ => OpcodeControl::None
+            }
+        }
+    }
+
+    fn inline_asm_reference(&self) -> bool {
+        self.inline_asm.unwrap_or(self.identifier == "inlineasm")
+    }
+
+    fn forbidden_branch_target(&self) -> bool {
+        self.branch_target.map_or(self.identifier == "reserved", | target | !target)
+    }
+
+    fn constant_push(&self) -> bool {
+        self.constant_push.unwrap_or(matches!(self.identifier.as_str(), "pushi64" | "pushu64" | "pushf32" | "pushf64"))
+    }
+
+    fn fold_arity(&self) -> Option<usize> {
+        self.fold_arity.or(match self.identifier.as_str() {
+            "ineg" | "bnot" => Some(1),
+            "iadd" | "isub" | "imul" | "idiv" | "imod" | "iequals" | "f32add" | "f64add" => Some(2),
+            _ => None
+        })
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self.klass {
+            OpcodeClass::None => "",
+            OpcodeClass::UInteger32 | OpcodeClass::FunctionReference => "(_, _)",
+            _ => "(_)"
+        }
+    }
+}
+
+fn gen_argument_accessor<F>(output_writer: &mut fs::File, opcodes: &Opcodes, name: &str, return_type: &str, pattern: &str, value: &str, filter: F)
+where F: Fn(&Opcode) -> bool {
+    let matching = opcodes.opcodes.iter()
+        .filter(| opcode | filter(opcode))
+        .collect::<Vec<_>>();
+    writeln!(output_writer, "    pub fn {}(&mut self) -> Option<{}> {{", name, return_type).unwrap();
+    if matching.is_empty() {
+        writeln!(output_writer, "        None").unwrap();
+    } else {
+        writeln!(output_writer, "        match self {{").unwrap();
+        for opcode in matching {
+            writeln!(output_writer, "            IRInstruction::{}{} => Some({}),", opcode.identifier, pattern, value).unwrap();
+        }
+        writeln!(output_writer, "            _ => None").unwrap();
+        writeln!(output_writer, "        }}").unwrap();
+    }
+    writeln!(output_writer, "    }}\n").unwrap();
+}
+
+fn gen_control_classifier(output_writer: &mut fs::File, opcodes: &Opcodes, name: &str, controls: &[OpcodeControl]) {
+    let patterns = opcodes.opcodes.iter()
+        .filter(| opcode | controls.contains(&opcode.control()))
+        .map(| opcode | format!("IRInstruction::{}{}", opcode.identifier, opcode.pattern()))
+        .collect::<Vec<_>>();
+    writeln!(output_writer, "    pub fn {}(&self) -> bool {{", name).unwrap();
+    if patterns.is_empty() {
+        writeln!(output_writer, "        false").unwrap();
+    } else {
+        writeln!(output_writer, "        matches!(self, {})", patterns.join(" | ")).unwrap();
+    }
+    writeln!(output_writer, "    }}\n").unwrap();
+}
+
+fn gen_opcode_classifier<F>(output_writer: &mut fs::File, opcodes: &Opcodes, name: &str, filter: F)
+where F: Fn(&Opcode) -> bool {
+    let patterns = opcodes.opcodes.iter()
+        .filter(| opcode | filter(opcode))
+        .map(| opcode | format!("IRInstruction::{}{}", opcode.identifier, opcode.pattern()))
+        .collect::<Vec<_>>();
+    writeln!(output_writer, "    pub fn {}(&self) -> bool {{", name).unwrap();
+    if patterns.is_empty() {
+        writeln!(output_writer, "        false").unwrap();
+    } else {
+        writeln!(output_writer, "        matches!(self, {})", patterns.join(" | ")).unwrap();
+    }
+    write!(output_writer, "    }}\n\n").unwrap();
+}
+
+fn gen_fold_arity(output_writer: &mut fs::File, opcodes: &Opcodes) {
+    let foldable = opcodes.opcodes.iter()
+        .filter_map(| opcode | opcode.fold_arity().map(| arity | (opcode, arity)))
+        .collect::<Vec<_>>();
+    writeln!(output_writer, "    pub fn fold_arity(&self) -> Option<usize> {{").unwrap();
+    if foldable.is_empty() {
+        writeln!(output_writer, "        None").unwrap();
+    } else {
+        writeln!(output_writer, "        match self {{").unwrap();
+        for (opcode, arity) in foldable {
+            writeln!(output_writer, "            IRInstruction::{}{} => Some({}),", opcode.identifier, opcode.pattern(), arity).unwrap();
+        }
+        writeln!(output_writer, "            _ => None").unwrap();
+        writeln!(output_writer, "        }}").unwrap();
+    }
+    write!(output_writer, "    }}\n\n").unwrap();
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,34 +226,48 @@ fn gen_opcodes(input_path: &Path, out_dir: &OsString) {
     let opcodes: Opcodes = quick_xml::de::from_reader(opcodes_reader).unwrap();
 
     let mut output_writer = fs::File::create(dest_path).unwrap();
-    write!(&mut output_writer, "#[allow(non_camel_case_types)]\n").unwrap();
-    write!(&mut output_writer, "#[derive(Debug)]\n").unwrap();
-    write!(&mut output_writer, "pub enum IRInstruction {{\n").unwrap();
+    writeln!(&mut output_writer, "#[allow(non_camel_case_types)]").unwrap();
+    writeln!(&mut output_writer, "#[derive(Debug, Clone)]").unwrap();
+    writeln!(&mut output_writer, "#[non_exhaustive]").unwrap();
+    writeln!(&mut output_writer, "pub enum IRInstruction {{").unwrap();
+    for opcode in opcodes.opcodes.iter() {
+        writeln!(&mut output_writer, "    /// `{}` (opcode {})", opcode.mnemonic, opcode.code).unwrap();
+        writeln!(&mut output_writer, "    {}{},", opcode.identifier, opcode.klass.fields()).unwrap();
+    }
+    write!(&mut output_writer, "}}\n\n").unwrap();
+
+    writeln!(&mut output_writer, "#[allow(non_camel_case_types, dead_code)]").unwrap();
+    writeln!(&mut output_writer, "#[derive(serde::Serialize, serde::Deserialize)]").unwrap();
+    writeln!(&mut output_writer, "#[serde(remote = \"IRInstruction\")]").unwrap();
+    writeln!(&mut output_writer, "pub(crate) enum IRInstructionDef {{").unwrap();
     for opcode in opcodes.opcodes.iter() {
-        write!(&mut output_writer, "    {}", opcode.identifier).unwrap();
+        writeln!(&mut output_writer, "    {}{},", opcode.identifier, opcode.klass.fields()).unwrap();
+    }
+    writeln!(&mut output_writer, "}}\n").unwrap();
+
+    writeln!(&mut output_writer, "impl PartialEq for IRInstruction {{").unwrap();
+    writeln!(&mut output_writer, "    fn eq(&self, other: &Self) -> bool {{").unwrap();
+    writeln!(&mut output_writer, "        match (self, other) {{").unwrap();
+    for opcode in opcodes.opcodes.iter() {
+        write!(&mut output_writer, "            (IRInstruction::{}", opcode.identifier).unwrap();
         match opcode.klass {
-            OpcodeClass::None => (),
-            OpcodeClass::Integer64 => write!(&mut output_writer, "(i64)").unwrap(),
-            OpcodeClass::UInteger64 | OpcodeClass::String
-                => write!(&mut output_writer, "(u64)").unwrap(),
-            OpcodeClass::CodeReference
-                => write!(&mut output_writer, "(usize)").unwrap(),
-            OpcodeClass::Identifier => write!(&mut output_writer, "(String)").unwrap(),
-            OpcodeClass::FunctionReference => write!(&mut output_writer, "(u64, Option<String>)").unwrap(),
-            OpcodeClass::UInteger32 => write!(&mut output_writer, "(u32, u32)").unwrap(),
-            OpcodeClass::TypeReference => write!(&mut output_writer, "(IRTypeRef)").unwrap(),
-            OpcodeClass::Float32 => write!(&mut output_writer, "(f32)").unwrap(),
-            OpcodeClass::Float64 => write!(&mut output_writer, "(f64)").unwrap(),
-            OpcodeClass::Boolean => write!(&mut output_writer, "(bool)").unwrap(),
-            OpcodeClass::MemFlags => write!(&mut output_writer, "(IRInstructionMemFlags)").unwrap()
+            OpcodeClass::None
+                => writeln!(&mut output_writer, ", IRInstruction::{}) => true,", opcode.identifier).unwrap(),
+            OpcodeClass::Float32 | OpcodeClass::Float64
+                => writeln!(&mut output_writer, "(x), IRInstruction::{}(y)) => x.to_bits() == y.to_bits(),", opcode.identifier).unwrap(),
+            OpcodeClass::UInteger32 | OpcodeClass::FunctionReference
+                => writeln!(&mut output_writer, "(x1, x2), IRInstruction::{}(y1, y2)) => x1 == y1 && x2 == y2,", opcode.identifier).unwrap(),
+            _ => writeln!(&mut output_writer, "(x), IRInstruction::{}(y)) => x == y,", opcode.identifier).unwrap()
         };
-        write!(&mut output_writer, ",\n").unwrap();
     }
-    write!(&mut output_writer, "}}\n\n").unwrap();
+    writeln!(&mut output_writer, "            _ => false").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    writeln!(&mut output_writer, "    }}").unwrap();
+    writeln!(&mut output_writer, "}}\n").unwrap();
 
-    write!(&mut output_writer, "impl IRInstruction {{\n").unwrap();
-    write!(&mut output_writer, "    pub fn code(&self) -> u64 {{\n").unwrap();
-    write!(&mut output_writer, "        match self {{\n").unwrap();
+    writeln!(&mut output_writer, "impl IRInstruction {{").unwrap();
+    writeln!(&mut output_writer, "    pub fn code(&self) -> u64 {{").unwrap();
+    writeln!(&mut output_writer, "        match self {{").unwrap();
     for opcode in opcodes.opcodes.iter() {
         write!(&mut output_writer, "            IRInstruction::{}", opcode.identifier).unwrap();
         match opcode.klass {
@@ -102,12 +280,12 @@ fn gen_opcodes(input_path: &Path, out_dir: &OsString) {
             OpcodeClass::UInteger32 | OpcodeClass::FunctionReference
               => write!(&mut output_writer, "(_, _)").unwrap(),
         };
-        write!(&mut output_writer, " => {},\n", opcode.code).unwrap();
+        writeln!(&mut output_writer, " => {},", opcode.code).unwrap();
     }
-    write!(&mut output_writer, "        }}\n").unwrap();
-    write!(&mut output_writer, "    }}\n\n").unwrap();
-    write!(&mut output_writer, "    pub fn mnemonic(&self) -> &'static str {{\n").unwrap();
-    write!(&mut output_writer, "        match self {{\n").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    writeln!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn mnemonic(&self) -> &'static str {{").unwrap();
+    writeln!(&mut output_writer, "        match self {{").unwrap();
     for opcode in opcodes.opcodes.iter() {
         write!(&mut output_writer, "            IRInstruction::{}", opcode.identifier).unwrap();
         match opcode.klass {
@@ -120,38 +298,109 @@ fn gen_opcodes(input_path: &Path, out_dir: &OsString) {
             OpcodeClass::UInteger32 | OpcodeClass::FunctionReference
                 => write!(&mut output_writer, "(_, _)").unwrap(),
         };
-        write!(&mut output_writer, " => \"{}\",\n", opcode.mnemonic).unwrap();
+        writeln!(&mut output_writer, " => \"{}\",", opcode.mnemonic).unwrap();
     }
-    write!(&mut output_writer, "        }}\n").unwrap();
-    write!(&mut output_writer, "    }}\n\n").unwrap();
-    write!(&mut output_writer, "    pub fn argument<'a>(&'a self) -> IRInstructionArgument<'a> {{\n").unwrap();
-    write!(&mut output_writer, "        match self {{\n").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    writeln!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn argument<'a>(&'a self) -> IRInstructionArgument<'a> {{").unwrap();
+    writeln!(&mut output_writer, "        match self {{").unwrap();
     for opcode in opcodes.opcodes.iter() {
         write!(&mut output_writer, "            IRInstruction::{}", opcode.identifier).unwrap();
         match opcode.klass {
-            OpcodeClass::None => write!(&mut output_writer, " => IRInstructionArgument::None,\n").unwrap(),
-            OpcodeClass::Integer64 => write!(&mut output_writer, "(x) => IRInstructionArgument::Integer(*x),\n").unwrap(),
-            OpcodeClass::UInteger64 => write!(&mut output_writer, "(x) => IRInstructionArgument::UInteger(*x),\n").unwrap(),
-            OpcodeClass::UInteger32 => write!(&mut output_writer, "(x, y) => IRInstructionArgument::UIntegerPair(*x, *y),\n").unwrap(),
-            OpcodeClass::Float64 => write!(&mut output_writer, "(x) => IRInstructionArgument::Float64(*x),\n").unwrap(),
-            OpcodeClass::Float32 => write!(&mut output_writer, "(x) => IRInstructionArgument::Float32(*x),\n").unwrap(),
-            OpcodeClass::Boolean => write!(&mut output_writer, "(x) => IRInstructionArgument::Boolean(*x),\n").unwrap(),
-            OpcodeClass::String => write!(&mut output_writer, "(x) => IRInstructionArgument::String(*x),\n").unwrap(),
-            OpcodeClass::Identifier => write!(&mut output_writer, "(id) => IRInstructionArgument::Identifier(id),\n").unwrap(),
-            OpcodeClass::TypeReference => write!(&mut output_writer, "(x) => IRInstructionArgument::TypeRef(*x),\n").unwrap(),
-            OpcodeClass::CodeReference => write!(&mut output_writer, "(x) => IRInstructionArgument::CodeRef(*x),\n").unwrap(),
-            OpcodeClass::FunctionReference => write!(&mut output_writer, "(id, name) => IRInstructionArgument::FunctionRef(*id, name.as_ref()),\n").unwrap(),
-            OpcodeClass::MemFlags => write!(&mut output_writer, "(memflags) => IRInstructionArgument::MemFlags(*memflags),\n").unwrap(),
+            OpcodeClass::None => writeln!(&mut output_writer, " => IRInstructionArgument::None,").unwrap(),
+            OpcodeClass::Integer64 => writeln!(&mut output_writer, "(x) => IRInstructionArgument::Integer(*x),").unwrap(),
+            OpcodeClass::UInteger64 => writeln!(&mut output_writer, "(x) => IRInstructionArgument::UInteger(*x),").unwrap(),
+            OpcodeClass::UInteger32 => writeln!(&mut output_writer, "(x, y) => IRInstructionArgument::UIntegerPair(*x, *y),").unwrap(),
+            OpcodeClass::Float64 => writeln!(&mut output_writer, "(x) => IRInstructionArgument::Float64(*x),").unwrap(),
+            OpcodeClass::Float32 => writeln!(&mut output_writer, "(x) => IRInstructionArgument::Float32(*x),").unwrap(),
+            OpcodeClass::Boolean => writeln!(&mut output_writer, "(x) => IRInstructionArgument::Boolean(*x),").unwrap(),
+            OpcodeClass::String => writeln!(&mut output_writer, "(x) => IRInstructionArgument::String(*x),").unwrap(),
+            OpcodeClass::Identifier => writeln!(&mut output_writer, "(id) => IRInstructionArgument::Identifier(id),").unwrap(),
+            OpcodeClass::TypeReference => writeln!(&mut output_writer, "(x) => IRInstructionArgument::TypeRef(*x),").unwrap(),
+            OpcodeClass::CodeReference => writeln!(&mut output_writer, "(x) => IRInstructionArgument::CodeRef(*x),").unwrap(),
+            OpcodeClass::FunctionReference => writeln!(&mut output_writer, "(id, name) => IRInstructionArgument::FunctionRef(*id, name.as_ref()),").unwrap(),
+            OpcodeClass::MemFlags => writeln!(&mut output_writer, "(memflags) => IRInstructionArgument::MemFlags(*memflags),").unwrap(),
         };
     }
-    write!(&mut output_writer, "        }}\n").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    writeln!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn class_of_mnemonic(mnemonic: &str) -> Option<OpcodeArgClass> {{").unwrap();
+    writeln!(&mut output_writer, "        match mnemonic {{").unwrap();
+    for opcode in opcodes.opcodes.iter() {
+        writeln!(&mut output_writer, "            \"{}\" => Some(OpcodeArgClass::{:?}),", opcode.mnemonic, opcode.klass).unwrap();
+    }
+    writeln!(&mut output_writer, "            _ => None").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    writeln!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn code_of_mnemonic(mnemonic: &str) -> Option<u64> {{").unwrap();
+    writeln!(&mut output_writer, "        match mnemonic {{").unwrap();
+    for opcode in opcodes.opcodes.iter() {
+        writeln!(&mut output_writer, "            \"{}\" => Some({}),", opcode.mnemonic, opcode.code).unwrap();
+    }
+    writeln!(&mut output_writer, "            _ => None").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    write!(&mut output_writer, "    }}\n\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn from_parts(mnemonic: &str, arg: IRInstructionArgumentOwned) -> Result<IRInstruction, IRError> {{").unwrap();
+    writeln!(&mut output_writer, "        match (mnemonic, arg) {{").unwrap();
+    for opcode in opcodes.opcodes.iter() {
+        let (pattern, fields) = opcode.klass.owned_argument();
+        writeln!(&mut output_writer, "            (\"{}\", IRInstructionArgumentOwned::{}) => Ok(IRInstruction::{}{}),",
+            opcode.mnemonic, pattern, opcode.identifier, fields).unwrap();
+    }
+    writeln!(&mut output_writer, "            (mnemonic, _) if IRInstruction::code_of_mnemonic(mnemonic).is_some()").unwrap();
+    writeln!(&mut output_writer, "                => Err(IRError::InstructionArgumentMismatch(mnemonic.to_owned())),").unwrap();
+    writeln!(&mut output_writer, "            (mnemonic, _) => Err(IRError::UnknownOpcode(mnemonic.to_owned()))").unwrap();
+    writeln!(&mut output_writer, "        }}").unwrap();
+    write!(&mut output_writer, "    }}\n\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn all_mnemonics() -> &'static [&'static str] {{").unwrap();
+    writeln!(&mut output_writer, "        &[{}]", opcodes.opcodes.iter()
+        .map(| opcode | format!("\"{}\"", opcode.mnemonic))
+        .collect::<Vec<_>>()
+        .join(", ")).unwrap();
+    writeln!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn all_opcode_codes() -> &'static [u64] {{").unwrap();
+    writeln!(&mut output_writer, "        &[{}]", opcodes.opcodes.iter()
+        .map(| opcode | opcode.code.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")).unwrap();
+    write!(&mut output_writer, "    }}\n\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn discriminant(&self) -> u32 {{").unwrap();
+    writeln!(&mut output_writer, "        match self {{").unwrap();
+    for (index, opcode) in opcodes.opcodes.iter().enumerate() {
+        writeln!(&mut output_writer, "            IRInstruction::{}{} => {},", opcode.identifier, opcode.pattern(), index).unwrap();
+    }
+    writeln!(&mut output_writer, "        }}").unwrap();
+    write!(&mut output_writer, "    }}\n\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn mnemonic_of_discriminant(discriminant: u32) -> Option<&'static str> {{").unwrap();
+    writeln!(&mut output_writer, "        IRInstruction::all_mnemonics().get(discriminant as usize).copied()").unwrap();
+    write!(&mut output_writer, "    }}\n\n").unwrap();
+    writeln!(&mut output_writer, "    pub fn code_of_discriminant(discriminant: u32) -> Option<u64> {{").unwrap();
+    writeln!(&mut output_writer, "        IRInstruction::all_opcode_codes().get(discriminant as usize).copied()").unwrap();
     write!(&mut output_writer, "    }}\n\n").unwrap();
-    write!(&mut output_writer, "    pub fn revision() -> Option<u64> {{\n").unwrap();
+    gen_argument_accessor(&mut output_writer, &opcodes, "code_ref_mut", "&mut usize", "(x)", "x",
+        | opcode | opcode.klass == OpcodeClass::CodeReference);
+    gen_argument_accessor(&mut output_writer, &opcodes, "type_ref_mut", "&mut IRTypeRef", "(x)", "x",
+        | opcode | opcode.klass == OpcodeClass::TypeReference);
+    gen_argument_accessor(&mut output_writer, &opcodes, "string_ref_mut", "&mut u64", "(x)", "x",
+        | opcode | opcode.klass == OpcodeClass::String);
+    gen_argument_accessor(&mut output_writer, &opcodes, "identifier_mut", "&mut String", "(x)", "x",
+        | opcode | opcode.klass == OpcodeClass::Identifier);
+    gen_argument_accessor(&mut output_writer, &opcodes, "function_ref_mut", "(&mut u64, &mut Option<String>)", "(x, y)", "(x, y)",
+        | opcode | opcode.klass == OpcodeClass::FunctionReference);
+    gen_argument_accessor(&mut output_writer, &opcodes, "inline_asm_ref_mut", "&mut u64", "(x)", "x",
+        | opcode | opcode.klass == OpcodeClass::UInteger64 && opcode.inline_asm_reference());
+    gen_control_classifier(&mut output_writer, &opcodes, "is_branch", &[OpcodeControl::Branch, OpcodeControl::Jump]);
+    gen_control_classifier(&mut output_writer, &opcodes, "is_terminator", &[OpcodeControl::Jump, OpcodeControl::Terminator]);
+    gen_control_classifier(&mut output_writer, &opcodes, "is_call", &[OpcodeControl::Call]);
+    gen_opcode_classifier(&mut output_writer, &opcodes, "is_constant_push", | opcode | opcode.constant_push());
+    gen_opcode_classifier(&mut output_writer, &opcodes, "is_forbidden_branch_target", | opcode | opcode.forbidden_branch_target());
+    gen_fold_arity(&mut output_writer, &opcodes);
+    writeln!(&mut output_writer, "    pub fn revision() -> Option<u64> {{").unwrap();
     match opcodes.revision {
-        Some(rev) => write!(&mut output_writer, "        Some({})\n", rev).unwrap(),
-        None => write!(&mut output_writer, "        None\n").unwrap(),
+        Some(rev) => writeln!(&mut output_writer, "        Some({})", rev).unwrap(),
+        None => writeln!(&mut output_writer, "        None").unwrap(),
     };
-    write!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "    }}").unwrap();
     write!(&mut output_writer, "}}").unwrap();
 
 }
@@ -164,40 +413,40 @@ fn gen_instr_loader(input_path: &Path, out_dir: &OsString) {
     let opcodes: Opcodes = quick_xml::de::from_reader(opcodes_reader).unwrap();
 
     let mut output_writer = fs::File::create(dest_path).unwrap();
-    write!(&mut output_writer, "fn deserialize_instruction<'de, D>(opcode_sym: &str, value: &Value) -> Result<IRInstruction, D::Error> where D: Deserializer<'de> {{\n").unwrap();
-    write!(&mut output_writer, "    match opcode_sym {{\n").unwrap();
+    writeln!(&mut output_writer, "fn deserialize_instruction<E>(opcode_sym: &str, value: &Value) -> Result<IRInstruction, E> where E: Error {{").unwrap();
+    writeln!(&mut output_writer, "    match opcode_sym {{").unwrap();
     for opcode in opcodes.opcodes.iter() {
         write!(&mut output_writer, "        \"{}\" => Ok(", opcode.mnemonic).unwrap();
         match opcode.klass {
             OpcodeClass::None 
                 => write!(&mut output_writer, "IRInstruction::{}", opcode.identifier).unwrap(),
             OpcodeClass::Integer64
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_i64::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_i64::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::UInteger64 | OpcodeClass::String
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_u64::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_u64::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::CodeReference
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_usize::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_usize::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::UInteger32
-                => write!(&mut output_writer, "{{ let (x, y) = deserialize_instr_u32::<D>(value)?; IRInstruction::{}(x, y) }}", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "{{ let (x, y) = deserialize_instr_u32::<E>(value)?; IRInstruction::{}(x, y) }}", opcode.identifier).unwrap(),
             OpcodeClass::FunctionReference
-                => write!(&mut output_writer, "{{ let (x, y) = deserialize_instr_funcref::<D>(value)?; IRInstruction::{}(x, y) }}", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "{{ let (x, y) = deserialize_instr_funcref::<E>(value)?; IRInstruction::{}(x, y) }}", opcode.identifier).unwrap(),
             OpcodeClass::Identifier
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_identifier::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_identifier::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::TypeReference
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_typeref::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_typeref::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::Float32
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_f32::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_f32::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::Float64
-            => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_f64::<D>(value)?)", opcode.identifier).unwrap(),
+            => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_f64::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::Boolean
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_bool::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_bool::<E>(value)?)", opcode.identifier).unwrap(),
             OpcodeClass::MemFlags
-                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_memflags::<D>(value)?)", opcode.identifier).unwrap(),
+                => write!(&mut output_writer, "IRInstruction::{}(deserialize_instr_memflags::<E>(value)?)", opcode.identifier).unwrap(),
         };
-        write!(&mut output_writer, "),\n").unwrap();
+        writeln!(&mut output_writer, "),").unwrap();
     }
-    write!(&mut output_writer, "        &_ => Err(D::Error::custom(\"Unable to deserialize instruction\".to_owned())).unwrap()\n").unwrap();
-    write!(&mut output_writer, "    }}\n").unwrap();
+    writeln!(&mut output_writer, "        &_ => Err(E::custom(format!(\"unknown opcode {{}}\", opcode_sym)))").unwrap();
+    writeln!(&mut output_writer, "    }}").unwrap();
     write!(&mut output_writer, "}}").unwrap();
 }
 
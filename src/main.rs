@@ -1,11 +1,16 @@
+use std::env;
 use std::io;
-use serde_json;
 
 pub mod okroshka;
 
 fn main() {
-    let module: okroshka::ir::module::IRModule =
-        serde_json::from_reader(io::stdin()).unwrap();
+    let module = okroshka::ir::module::IRModule::from_json_reader(io::stdin()).unwrap();
 
-    println!("{:?}", module);
+    if env::args().skip(1).any(| arg | arg == "--disassemble") {
+        let mut listing = String::new();
+        module.disassemble(&mut listing).unwrap();
+        print!("{}", listing);
+    } else {
+        println!("{:?}", module);
+    }
 }
@@ -1,12 +1,13 @@
 use std::collections::HashSet;
 use std::collections::HashMap;
 use std::iter::Iterator;
+use serde::{Deserialize, Serialize};
 
 use crate::okroshka::ir::{IRIdentifier, IRTypeRef};
 
 use super::IRError;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IRInlineAssemblyParameterClass {
     ImmediateConstant(IRTypeRef, i64),
     ImmediateIdentifierBased(IRTypeRef, String, i64),
@@ -18,7 +19,7 @@ pub enum IRInlineAssemblyParameterClass {
     ReadStore(IRTypeRef, u64, IRTypeRef, u64)
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IRInlineAssemblyParameterConstraint {
     None,
     Register,
@@ -26,7 +27,7 @@ pub enum IRInlineAssemblyParameterConstraint {
     RegisterMemory
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IRInlineAssemblyParameter {
     id: IRIdentifier,
     aliases: Vec<String>,
@@ -34,7 +35,7 @@ pub struct IRInlineAssemblyParameter {
     constraint: IRInlineAssemblyParameterConstraint
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct IRInlineAssemblyJumpTarget {
     id: IRIdentifier,
     aliases: Vec<String>,
@@ -42,13 +43,19 @@ pub struct IRInlineAssemblyJumpTarget {
     target_offset: usize
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum IRInlineAssemblyIndexedAlias {
     Parameter(IRIdentifier),
     JumpTarget(IRIdentifier)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+pub enum IRInlineAssemblyAliasTarget<'a> {
+    Parameter(&'a IRInlineAssemblyParameter),
+    JumpTarget(&'a IRInlineAssemblyJumpTarget)
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRInlineAssembly {
     id: IRIdentifier,
     global: bool,
@@ -59,6 +66,34 @@ pub struct IRInlineAssembly {
     alias_index: HashMap<String, IRInlineAssemblyIndexedAlias>
 }
 
+impl IRInlineAssemblyParameterClass {
+    pub fn type_refs(&self) -> Vec<IRTypeRef> {
+        match self {
+            IRInlineAssemblyParameterClass::ImmediateConstant(typeref, _) |
+            IRInlineAssemblyParameterClass::ImmediateIdentifierBased(typeref, _, _) |
+            IRInlineAssemblyParameterClass::ImmediateLiteralBased(typeref, _, _) |
+            IRInlineAssemblyParameterClass::Read(typeref, _) |
+            IRInlineAssemblyParameterClass::Load(typeref, _) |
+            IRInlineAssemblyParameterClass::Store(typeref, _) |
+            IRInlineAssemblyParameterClass::LoadStore(typeref, _) => vec![*typeref],
+            IRInlineAssemblyParameterClass::ReadStore(typeref1, _, typeref2, _) => vec![*typeref1, *typeref2]
+        }
+    }
+
+    pub(crate) fn type_refs_mut(&mut self) -> Vec<&mut IRTypeRef> {
+        match self {
+            IRInlineAssemblyParameterClass::ImmediateConstant(typeref, _) |
+            IRInlineAssemblyParameterClass::ImmediateIdentifierBased(typeref, _, _) |
+            IRInlineAssemblyParameterClass::ImmediateLiteralBased(typeref, _, _) |
+            IRInlineAssemblyParameterClass::Read(typeref, _) |
+            IRInlineAssemblyParameterClass::Load(typeref, _) |
+            IRInlineAssemblyParameterClass::Store(typeref, _) |
+            IRInlineAssemblyParameterClass::LoadStore(typeref, _) => vec![typeref],
+            IRInlineAssemblyParameterClass::ReadStore(typeref1, _, typeref2, _) => vec![typeref1, typeref2]
+        }
+    }
+}
+
 impl IRInlineAssemblyParameter {
     pub fn new(id: IRIdentifier, aliases: Vec<String>, klass: IRInlineAssemblyParameterClass, constraint: IRInlineAssemblyParameterConstraint) -> IRInlineAssemblyParameter {
         IRInlineAssemblyParameter {
@@ -82,6 +117,10 @@ impl IRInlineAssemblyParameter {
         &self.klass
     }
 
+    pub(crate) fn klass_mut(&mut self) -> &mut IRInlineAssemblyParameterClass {
+        &mut self.klass
+    }
+
     pub fn constraint(&self) -> IRInlineAssemblyParameterConstraint {
         self.constraint
     }
@@ -110,6 +149,10 @@ impl IRInlineAssemblyJumpTarget {
         self.target_function.as_str()
     }
 
+    pub(crate) fn set_target_function(&mut self, target_function: String) {
+        self.target_function = target_function;
+    }
+
     pub fn target_function_offset(&self) -> usize {
         self.target_offset
     }
@@ -129,22 +172,22 @@ impl IRInlineAssembly {
 
         for (&param_id, param) in inline_asm.parameters.iter() {
             if param_id != param.identifier() {
-                Err(IRError("IR inline assembly parameter identifier does not match the index".to_owned()))?;
+                Err(IRError::InlineAssemblyParameterIdMismatch(param_id))?;
             }
             for alias in param.aliases() {
                 if inline_asm.alias_index.insert(alias.to_owned(), IRInlineAssemblyIndexedAlias::Parameter(param_id)).is_some() {
-                    Err(IRError("Detected duplicating IR inline assembly aliases".to_owned()))?;
+                    Err(IRError::DuplicateAlias(alias.to_owned()))?;
                 }
             }
         }
 
         for (&target_id, target) in inline_asm.jump_targets.iter() {
             if target_id != target.identifier() {
-                Err(IRError("IR inline assembly parameter identifier does not match the index".to_owned()))?;
+                Err(IRError::InlineAssemblyJumpTargetIdMismatch(target_id))?;
             }
             for alias in target.aliases() {
                 if inline_asm.alias_index.insert(alias.to_owned(), IRInlineAssemblyIndexedAlias::JumpTarget(target_id)).is_some() {
-                    Err(IRError("Detected duplicating IR inline assembly aliases".to_owned()))?;
+                    Err(IRError::DuplicateAlias(alias.to_owned()))?;
                 }
             }
         }
@@ -156,6 +199,10 @@ impl IRInlineAssembly {
         self.id
     }
 
+    pub(crate) fn set_identifier(&mut self, id: IRIdentifier) {
+        self.id = id;
+    }
+
     pub fn is_global(&self) -> bool {
         self.global
     }
@@ -169,8 +216,12 @@ impl IRInlineAssembly {
     }
 
     pub fn parameters(&self) -> impl Iterator<Item=&IRInlineAssemblyParameter> {
+        self.parameters.values()
+    }
+
+    pub(crate) fn parameters_mut(&mut self) -> impl Iterator<Item=&mut IRInlineAssemblyParameter> {
         self.parameters
-            .iter()
+            .iter_mut()
             .map(| (_, param) | param)
     }
 
@@ -183,17 +234,112 @@ impl IRInlineAssembly {
             .map(| x | x.as_str())
     }
 
+    pub fn clobbers_sorted(&self) -> Vec<&str> {
+        let mut clobbers = self.clobbers().collect::<Vec<_>>();
+        clobbers.sort_unstable();
+        clobbers
+    }
+
     pub fn get_jump_target(&self, id: IRIdentifier) -> Option<&IRInlineAssemblyJumpTarget> {
         self.jump_targets.get(&id)
     }
 
     pub fn jump_targets(&self) -> impl Iterator<Item=&IRInlineAssemblyJumpTarget> {
+        self.jump_targets.values()
+    }
+
+    pub(crate) fn jump_targets_mut(&mut self) -> impl Iterator<Item=&mut IRInlineAssemblyJumpTarget> {
         self.jump_targets
-            .iter()
+            .iter_mut()
             .map(| (_, target) | target)
     }
 
     pub fn get_by_alias(&self, alias: &str) -> Option<IRInlineAssemblyIndexedAlias> {
-        self.alias_index.get(alias).map(| x | *x)
+        self.alias_index.get(alias).copied()
     }
-}
\ No newline at end of file
+
+    pub fn template_placeholders(&self) -> Vec<&str> {
+        let mut placeholders = Vec::new();
+        let mut rest = self.template.as_str();
+        while let Some(position) = rest.find('%') {
+            let directive = &rest[position + 1..];
+            if let Some(escaped) = directive.strip_prefix('%') {
+                rest = escaped;
+                continue;
+            }
+            let modifiers = directive.find(| c: char | !c.is_ascii_alphabetic()).unwrap_or(directive.len());
+            let operand = &directive[modifiers..];
+            let digits = operand.find(| c: char | !c.is_ascii_digit()).unwrap_or(operand.len());
+            if digits > 0 {
+                placeholders.push(&operand[..digits]);
+                rest = &operand[digits..];
+                continue;
+            }
+            match operand.strip_prefix('[').and_then(| name | name.split_once(']')) {
+                Some((alias, remaining)) => {
+                    placeholders.push(alias);
+                    rest = remaining;
+                },
+                None => rest = directive
+            }
+        }
+        placeholders
+    }
+
+    pub fn resolve_alias(&self, alias: &str) -> Option<IRInlineAssemblyAliasTarget<'_>> {
+        match self.get_by_alias(alias)? {
+            IRInlineAssemblyIndexedAlias::Parameter(id)
+                => self.get_parameter(id).map(IRInlineAssemblyAliasTarget::Parameter),
+            IRInlineAssemblyIndexedAlias::JumpTarget(id)
+                => self.get_jump_target(id).map(IRInlineAssemblyAliasTarget::JumpTarget)
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline_asm_with_template(template: &str) -> Result<IRInlineAssembly, IRError> {
+        let param = IRInlineAssemblyParameter::new(0, vec!["0".to_owned(), "x".to_owned()],
+            IRInlineAssemblyParameterClass::Read(IRTypeRef::new(0, 0), 0),
+            IRInlineAssemblyParameterConstraint::Register);
+        let target = IRInlineAssemblyJumpTarget::new(0, vec!["l1".to_owned()], "main".to_owned(), 0);
+        IRInlineAssembly::new(0, false, template.to_owned(), HashMap::from([(0, param)]),
+            HashSet::new(), HashMap::from([(0, target)]))
+    }
+
+    #[test]
+    fn resolve_alias_borrows_target() {
+        let inline_asm = inline_asm_with_template("mov %[x], %[x]").unwrap();
+        match inline_asm.resolve_alias("x") {
+            Some(IRInlineAssemblyAliasTarget::Parameter(param)) => {
+                assert_eq!(param.identifier(), 0);
+                assert_eq!(param.constraint(), IRInlineAssemblyParameterConstraint::Register);
+            },
+            other => panic!("unexpected alias target {:?}", other)
+        }
+        assert!(matches!(inline_asm.resolve_alias("l1"), Some(IRInlineAssemblyAliasTarget::JumpTarget(_))));
+        assert!(inline_asm.resolve_alias("y").is_none());
+    }
+
+    #[test]
+    fn template_placeholders_in_order() {
+        let inline_asm = inline_asm_with_template("mov %[x], %0; add %b[x], %%eax; jmp %l[l1]; %=").unwrap();
+        assert_eq!(inline_asm.template_placeholders(), vec!["x", "0", "x", "l1"]);
+    }
+
+    #[test]
+    fn template_placeholders_numeric_forms() {
+        let inline_asm = inline_asm_with_template("mov %0, %w0\njmp %l1").unwrap();
+        assert_eq!(inline_asm.template_placeholders(), vec!["0", "0", "1"]);
+    }
+
+    #[test]
+    fn clobbers_sorted_orders_lexicographically() {
+        let clobbers = ["rdx", "memory", "cc", "rax"].iter()
+            .map(| clobber | clobber.to_string())
+            .collect();
+        let inline_asm = IRInlineAssembly::new(0, true, "cpuid".to_owned(), HashMap::new(), clobbers, HashMap::new()).unwrap();
+        assert_eq!(inline_asm.clobbers_sorted(), vec!["cc", "memory", "rax", "rdx"]);
+    }
+}
@@ -1,16 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
 use std::vec::Vec;
 
-use crate::okroshka::ir::instr::IRInstruction;
+use crate::okroshka::ir::instr::{IRInstruction, IRInstructionArgument};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRBlock {
-    code: Vec<IRInstruction>
+    code: Vec<IRInstruction>,
+    metadata: BTreeMap<usize, serde_json::Value>
 }
 
 impl IRBlock {
     pub fn new(code: Vec<IRInstruction>) -> IRBlock {
         IRBlock {
-            code
+            code,
+            metadata: BTreeMap::new()
         }
     }
 
@@ -25,4 +29,154 @@ impl IRBlock {
     pub fn len(&self) -> usize {
         self.code.len()
     }
-}
\ No newline at end of file
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    pub fn metadata_at(&self, index: usize) -> Option<&serde_json::Value> {
+        self.metadata.get(&index)
+    }
+
+    pub fn metadata(&self) -> impl Iterator<Item = (usize, &serde_json::Value)> {
+        self.metadata.iter()
+            .map(| (&index, metadata) | (index, metadata))
+    }
+
+    pub fn set_metadata(&mut self, index: usize, metadata: serde_json::Value) {
+        self.metadata.insert(index, metadata);
+    }
+
+    pub fn code_mut(&mut self) -> impl Iterator<Item = &mut IRInstruction> {
+        self.code.iter_mut()
+    }
+
+    pub fn at_mut(&mut self, index: usize) -> Option<&mut IRInstruction> {
+        self.code.get_mut(index)
+    }
+
+    pub fn push(&mut self, instr: IRInstruction) {
+        self.code.push(instr);
+    }
+
+    pub fn insert(&mut self, index: usize, instr: IRInstruction) {
+        self.code.insert(index, instr);
+        let shifted = self.metadata.split_off(&index);
+        self.metadata.extend(shifted.into_iter().map(| (index, metadata) | (index + 1, metadata)));
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<IRInstruction> {
+        if index < self.code.len() {
+            let mut shifted = self.metadata.split_off(&index);
+            shifted.remove(&index);
+            self.metadata.extend(shifted.into_iter().map(| (index, metadata) | (index - 1, metadata)));
+            Some(self.code.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn remap_code_refs<F>(&mut self, mut remap: F)
+    where F: FnMut(usize) -> usize {
+        for instr in self.code.iter_mut() {
+            if let Some(target) = instr.code_ref_mut() {
+                *target = remap(*target);
+            }
+        }
+    }
+
+    pub fn insert_and_shift(&mut self, index: usize, instr: IRInstruction) {
+        self.remap_code_refs(| target | if target > index { target + 1 } else { target });
+        self.insert(index, instr);
+    }
+
+    pub fn remove_and_shift(&mut self, index: usize) -> Option<IRInstruction> {
+        let instr = self.remove(index)?;
+        self.remap_code_refs(| target | if target > index { target - 1 } else { target });
+        Some(instr)
+    }
+
+    pub fn basic_blocks(&self) -> Vec<Range<usize>> {
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+        for (index, instr) in self.code.iter().enumerate() {
+            if let IRInstructionArgument::CodeRef(target) = instr.argument() {
+                leaders.insert(target);
+            }
+            if instr.is_branch() || instr.is_terminator() {
+                leaders.insert(index + 1);
+            }
+        }
+
+        let leaders = leaders.into_iter()
+            .filter(| &index | index < self.code.len())
+            .collect::<Vec<_>>();
+        leaders.iter()
+            .enumerate()
+            .map(| (block, &begin) | begin..leaders.get(block + 1).copied().unwrap_or(self.code.len()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_blocks_split_at_forward_and_backward_branches() {
+        let block = IRBlock::new(vec![
+            IRInstruction::pushi64(1),
+            IRInstruction::branch(4),
+            IRInstruction::pushi64(2),
+            IRInstruction::jmp(1),
+            IRInstruction::ret
+        ]);
+        assert_eq!(block.basic_blocks(), vec![0..1, 1..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn basic_blocks_of_empty_block() {
+        assert!(IRBlock::new(Vec::new()).basic_blocks().is_empty());
+    }
+
+    #[test]
+    fn edit_instructions_in_place() {
+        let mut block = IRBlock::new(vec![IRInstruction::pushi64(1), IRInstruction::ret]);
+        block.insert(1, IRInstruction::ineg);
+        block.push(IRInstruction::ret);
+        assert_eq!(block.remove(3), Some(IRInstruction::ret));
+        assert_eq!(block.remove(3), None);
+        for instr in block.code_mut() {
+            if let IRInstruction::pushi64(value) = instr {
+                *value = 2;
+            }
+        }
+        assert_eq!(block, IRBlock::new(vec![IRInstruction::pushi64(2), IRInstruction::ineg, IRInstruction::ret]));
+    }
+
+    #[test]
+    fn insert_then_remove_restores_block() {
+        let original = IRBlock::new(vec![IRInstruction::pushi64(1), IRInstruction::branch(3), IRInstruction::pop, IRInstruction::ret]);
+        let mut block = original.clone();
+        block.insert(2, IRInstruction::pushi64(2));
+        block.remap_code_refs(| target | if target >= 2 { target + 1 } else { target });
+        assert_eq!(block.at(1), Some(&IRInstruction::branch(4)));
+        assert_eq!(block.remove(2), Some(IRInstruction::pushi64(2)));
+        block.remap_code_refs(| target | if target > 2 { target - 1 } else { target });
+        assert_eq!(block, original);
+    }
+
+    #[test]
+    fn insert_and_remove_keep_metadata_aligned() {
+        let mut block = IRBlock::new(vec![IRInstruction::pushi64(1), IRInstruction::pushi64(2), IRInstruction::ret]);
+        block.set_metadata(1, serde_json::json!("second"));
+        block.insert(0, IRInstruction::nop);
+        assert_eq!(block.metadata_at(1), None);
+        assert_eq!(block.metadata_at(2), Some(&serde_json::json!("second")));
+        block.remove(2);
+        assert_eq!(block.metadata().count(), 0);
+        block.set_metadata(2, serde_json::json!("ret"));
+        block.remove(0);
+        assert_eq!(block.metadata().collect::<Vec<_>>(), vec![(1, &serde_json::json!("ret"))]);
+    }
+}
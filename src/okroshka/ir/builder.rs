@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::rc::Rc;
+
+use crate::okroshka::ir::{
+    IRIdentifier,
+    IRError,
+    IRType,
+    IRFunctionDeclaration,
+    IRFunction,
+    IRSymbol,
+    IRStringLiteral,
+    IRData,
+    IRInlineAssembly,
+    IRModule
+};
+
+#[derive(Debug, Default)]
+pub struct IRModuleBuilder {
+    pub(crate) globals: HashMap<Rc<str>, IRSymbol>,
+    pub(crate) externals: HashMap<Rc<str>, IRSymbol>,
+    pub(crate) types: HashMap<IRIdentifier, IRType>,
+    pub(crate) string_literals: HashMap<IRIdentifier, IRStringLiteral>,
+    pub(crate) function_declarations: HashMap<IRIdentifier, IRFunctionDeclaration>,
+    pub(crate) functions: HashMap<Rc<str>, IRFunction>,
+    pub(crate) data: HashMap<Rc<str>, IRData>,
+    pub(crate) inline_asm: HashMap<IRIdentifier, IRInlineAssembly>
+}
+
+fn insert_unique<K, V, F>(map: &mut HashMap<K, V>, key: K, value: V, err: F) -> Result<(), IRError>
+where
+    K: std::hash::Hash + Eq,
+    F: FnOnce(&K) -> IRError {
+    match map.entry(key) {
+        Entry::Occupied(entry) => Err(err(entry.key())),
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+            Ok(())
+        }
+    }
+}
+
+impl IRModuleBuilder {
+    pub fn new() -> IRModuleBuilder {
+        IRModuleBuilder::default()
+    }
+
+    pub fn add_global(&mut self, symbol: IRSymbol) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.globals, symbol.shared_name(), symbol,
+            | name | IRError::DuplicateSymbol(name.to_string()))?;
+        Ok(self)
+    }
+
+    pub fn add_external(&mut self, symbol: IRSymbol) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.externals, symbol.shared_name(), symbol,
+            | name | IRError::DuplicateSymbol(name.to_string()))?;
+        Ok(self)
+    }
+
+    pub fn add_type(&mut self, tp: IRType) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.types, tp.identifier(), tp,
+            | &id | IRError::DuplicateTypeId(id))?;
+        Ok(self)
+    }
+
+    pub fn add_string_literal(&mut self, literal: IRStringLiteral) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.string_literals, literal.identifier(), literal,
+            | &id | IRError::DuplicateStringLiteralId(id))?;
+        Ok(self)
+    }
+
+    pub fn add_function_declaration(&mut self, decl: IRFunctionDeclaration) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.function_declarations, decl.identifier(), decl,
+            | &id | IRError::DuplicateFunctionDeclId(id))?;
+        Ok(self)
+    }
+
+    pub fn add_function(&mut self, func: IRFunction) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.functions, func.shared_name(), func,
+            | name | IRError::DuplicateFunction(name.to_string()))?;
+        Ok(self)
+    }
+
+    pub fn add_data(&mut self, data: IRData) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.data, data.shared_name(), data,
+            | name | IRError::DuplicateData(name.to_string()))?;
+        Ok(self)
+    }
+
+    pub fn add_inline_assembly(&mut self, inline_asm: IRInlineAssembly) -> Result<&mut IRModuleBuilder, IRError> {
+        insert_unique(&mut self.inline_asm, inline_asm.identifier(), inline_asm,
+            | &id | IRError::DuplicateInlineAssemblyId(id))?;
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<IRModule, IRError> {
+        IRModule::new(self.globals,
+            self.externals,
+            self.types,
+            self.string_literals,
+            self.function_declarations,
+            self.functions,
+            self.data,
+            self.inline_asm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::ir::{IRBlock, IRInstruction, IRSymbolAttributes};
+
+    fn builder_with_function() -> IRModuleBuilder {
+        let mut builder = IRModuleBuilder::new();
+        builder.add_type(IRType::new(0, Vec::new())).unwrap()
+            .add_function_declaration(IRFunctionDeclaration::new(0, Some("main".to_owned()), 0, false, 0)).unwrap()
+            .add_function(IRFunction::new("main".to_owned(), 0, 0, IRBlock::new(vec![IRInstruction::ret]))).unwrap()
+            .add_global(IRSymbol::Global(Rc::from("main"), IRSymbolAttributes::default())).unwrap();
+        builder
+    }
+
+    #[test]
+    fn build_module_in_memory() {
+        let module = builder_with_function().build().unwrap();
+        assert!(module.is_global("main"));
+        assert_eq!(module.get_function("main").map(| func | func.body().len()), Some(1));
+    }
+
+    #[test]
+    fn add_rejects_duplicate_type_id() {
+        let mut builder = builder_with_function();
+        assert!(matches!(builder.add_type(IRType::new(0, Vec::new())), Err(IRError::DuplicateTypeId(0))));
+    }
+
+    #[test]
+    fn build_validates_references() {
+        let mut builder = builder_with_function();
+        builder.add_function(IRFunction::new("other".to_owned(), 5, 0, IRBlock::new(Vec::new()))).unwrap();
+        match builder.build() {
+            Err(IRError::InFunction { function, error, .. }) => {
+                assert_eq!(function, "other");
+                assert!(matches!(*error, IRError::FunctionDeclNotFound(5)));
+            },
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+}
@@ -2,11 +2,163 @@ use std::fmt;
 
 pub type IRIdentifier = u64;
 
-#[derive(Debug, Clone)]
-pub struct IRError(pub String);
+#[derive(Debug, Clone, PartialEq)]
+pub enum IRError {
+    FunctionDeclIdMismatch(IRIdentifier),
+    FunctionNameMismatch(String),
+    DataNameMismatch(String),
+    InlineAssemblyIdMismatch(IRIdentifier),
+    InlineAssemblyParameterIdMismatch(IRIdentifier),
+    InlineAssemblyJumpTargetIdMismatch(IRIdentifier),
+    DuplicateAlias(String),
+    DuplicateGlobalAlias(String),
+    UnknownTemplateAlias(String),
+    DuplicateSymbol(String),
+    GlobalExternalConflict(String),
+    DuplicateTypeId(IRIdentifier),
+    DuplicateStringLiteralId(IRIdentifier),
+    DuplicateFunctionDeclId(IRIdentifier),
+    DuplicateFunction(String),
+    DuplicateData(String),
+    DuplicateInlineAssemblyId(IRIdentifier),
+    IdentifierOverflow,
+    JumpTargetFunctionNotFound(String),
+    JumpTargetOutOfRange { function: String, offset: usize, len: usize },
+    JumpTargetNotLeader { function: String, offset: usize },
+    GlobalInlineAssemblyJumpTargets(IRIdentifier),
+    CodeRefOutOfRange { target: usize, len: usize },
+    CodeRefForbiddenTarget { target: usize, mnemonic: String },
+    FunctionDeclNotFound(IRIdentifier),
+    FunctionNotFound(String),
+    StringLiteralNotFound(IRIdentifier),
+    SymbolNotFound(String),
+    TypeIdNotFound(IRIdentifier),
+    TypeIndexOutOfRange { type_id: IRIdentifier, index: usize, len: usize },
+    FunctionDeclTypeNotStruct { decl_id: IRIdentifier, type_id: IRIdentifier },
+    FunctionDeclNameMismatch { function: String, decl_id: IRIdentifier, decl_name: String },
+    MalformedType(IRIdentifier),
+    DataSizeExceedsType { data: String, size: u64, type_size: u64 },
+    DataSizeUnavailable(String),
+    TypeLayoutOverflow { type_id: IRIdentifier, index: usize },
+    TypeLayoutUnavailable { type_id: IRIdentifier, index: usize },
+    NonFiniteFloat(f64),
+    UnknownOpcode(String),
+    InstructionArgumentMismatch(String),
+    InFunctionDeclaration { decl_id: IRIdentifier, error: Box<IRError> },
+    InFunction { function: String, index: Option<usize>, error: Box<IRError> },
+    InData { data: String, index: Option<usize>, error: Box<IRError> },
+    InInlineAssembly { inline_asm_id: IRIdentifier, error: Box<IRError> }
+}
+
+impl IRError {
+    pub(crate) fn in_function_declaration(self, decl_id: IRIdentifier) -> IRError {
+        IRError::InFunctionDeclaration { decl_id, error: Box::new(self) }
+    }
+
+    pub(crate) fn in_function(self, function: &str, index: Option<usize>) -> IRError {
+        IRError::InFunction { function: function.to_owned(), index, error: Box::new(self) }
+    }
+
+    pub(crate) fn in_data(self, data: &str, index: Option<usize>) -> IRError {
+        IRError::InData { data: data.to_owned(), index, error: Box::new(self) }
+    }
+
+    pub(crate) fn in_inline_assembly(self, inline_asm_id: IRIdentifier) -> IRError {
+        IRError::InInlineAssembly { inline_asm_id, error: Box::new(self) }
+    }
+}
 
 impl fmt::Display for IRError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            IRError::FunctionDeclIdMismatch(_) | IRError::FunctionNameMismatch(_)
+                => write!(f, "Expected IR function declaration identifier to match respective index key"),
+            IRError::DataNameMismatch(_)
+                => write!(f, "Expected IR data name to match respective index key"),
+            IRError::InlineAssemblyIdMismatch(_)
+                => write!(f, "Expected IR inline assembly identifier to match respective index key"),
+            IRError::InlineAssemblyParameterIdMismatch(_) | IRError::InlineAssemblyJumpTargetIdMismatch(_)
+                => write!(f, "IR inline assembly parameter identifier does not match the index"),
+            IRError::DuplicateAlias(_)
+                => write!(f, "Detected duplicating IR inline assembly aliases"),
+            IRError::DuplicateGlobalAlias(alias)
+                => write!(f, "Detected duplicating IR inline assembly alias {} across global inline assembly", alias),
+            IRError::UnknownTemplateAlias(alias)
+                => write!(f, "IR inline assembly template references unknown alias {}", alias),
+            IRError::DuplicateSymbol(name)
+                => write!(f, "Detected duplicating IR symbol {}", name),
+            IRError::GlobalExternalConflict(name)
+                => write!(f, "IR symbol {} is declared both global and external", name),
+            IRError::DuplicateTypeId(id)
+                => write!(f, "Detected duplicating IR type identifier {}", id),
+            IRError::DuplicateStringLiteralId(id)
+                => write!(f, "Detected duplicating IR string literal identifier {}", id),
+            IRError::DuplicateFunctionDeclId(id)
+                => write!(f, "Detected duplicating IR function declaration identifier {}", id),
+            IRError::DuplicateFunction(name)
+                => write!(f, "Detected duplicating IR function {}", name),
+            IRError::DuplicateData(name)
+                => write!(f, "Detected duplicating IR data {}", name),
+            IRError::DuplicateInlineAssemblyId(id)
+                => write!(f, "Detected duplicating IR inline assembly identifier {}", id),
+            IRError::IdentifierOverflow
+                => write!(f, "Unable to allocate IR identifier beyond the representable range"),
+            IRError::JumpTargetFunctionNotFound(_)
+                => write!(f, "Unable to find specified jump target function"),
+            IRError::JumpTargetOutOfRange { .. }
+                => write!(f, "Expected IR inline assembly jump target offset exceeds respective function body length"),
+            IRError::JumpTargetNotLeader { function, offset }
+                => write!(f, "IR inline assembly jump target offset {} does not start a basic block of function {}", offset, function),
+            IRError::GlobalInlineAssemblyJumpTargets(id)
+                => write!(f, "Global IR inline assembly {} cannot declare jump targets into function bodies", id),
+            IRError::CodeRefOutOfRange { target, len }
+                => write!(f, "IR instruction argument code reference {} exceeds respective block boundaries ({})", target, len),
+            IRError::CodeRefForbiddenTarget { target, mnemonic }
+                => write!(f, "IR instruction argument code reference {} targets instruction {} which is not a valid branch target", target, mnemonic),
+            IRError::FunctionDeclNotFound(id)
+                => write!(f, "Provided IR function declaration identifier {} does not exist in the module", id),
+            IRError::FunctionNotFound(name)
+                => write!(f, "Provided IR function {} does not exist in the module", name),
+            IRError::StringLiteralNotFound(id)
+                => write!(f, "Provided IR string literal identifier {} does not exist in the module", id),
+            IRError::SymbolNotFound(name)
+                => write!(f, "Provided IR symbol {} does not exist in the module", name),
+            IRError::TypeIdNotFound(id)
+                => write!(f, "Provided IR type identifier {} does not exist in the module", id),
+            IRError::TypeIndexOutOfRange { type_id, index, len }
+                => write!(f, "Provided IR type index {} exceeds IR type {} length ({})", index, type_id, len),
+            IRError::FunctionDeclTypeNotStruct { decl_id, type_id }
+                => write!(f, "IR function declaration {} refers to type {} which is not a structure", decl_id, type_id),
+            IRError::FunctionDeclNameMismatch { function, decl_id, decl_name }
+                => write!(f, "IR function {} does not match the name {} of its declaration {}", function, decl_name, decl_id),
+            IRError::DataSizeExceedsType { data, size, type_size }
+                => write!(f, "IR data {} elements occupy {} bytes exceeding its type size {}", data, size, type_size),
+            IRError::DataSizeUnavailable(data)
+                => write!(f, "Unable to compute size of IR data {} elements", data),
+            IRError::MalformedType(id)
+                => write!(f, "IR type {} aggregate entries do not match their declared fields", id),
+            IRError::TypeLayoutOverflow { type_id, index }
+                => write!(f, "IR type {} entry {} size exceeds representable range", type_id, index),
+            IRError::TypeLayoutUnavailable { type_id, index }
+                => write!(f, "Unable to compute layout of IR type {} entry {}", type_id, index),
+            IRError::NonFiniteFloat(value)
+                => write!(f, "IR floating-point value {} is not finite", value),
+            IRError::UnknownOpcode(mnemonic)
+                => write!(f, "Unknown IR opcode {}", mnemonic),
+            IRError::InstructionArgumentMismatch(mnemonic)
+                => write!(f, "IR instruction {} argument does not match its opcode class", mnemonic),
+            IRError::InFunctionDeclaration { decl_id, error }
+                => write!(f, "in IR function declaration {}: {}", decl_id, error),
+            IRError::InFunction { function, index: Some(index), error }
+                => write!(f, "in IR function {}, instruction {}: {}", function, index, error),
+            IRError::InFunction { function, index: None, error }
+                => write!(f, "in IR function {}: {}", function, error),
+            IRError::InData { data, index: Some(index), error }
+                => write!(f, "in IR data {}, element {}: {}", data, index, error),
+            IRError::InData { data, index: None, error }
+                => write!(f, "in IR data {}: {}", data, error),
+            IRError::InInlineAssembly { inline_asm_id, error }
+                => write!(f, "in IR inline assembly {}: {}", inline_asm_id, error)
+        }
     }
 }
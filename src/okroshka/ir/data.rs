@@ -1,39 +1,94 @@
+use std::fmt;
+use std::rc::Rc;
 use std::vec::Vec;
+use serde::{Deserialize, Serialize};
 
-use crate::okroshka::ir::IRIdentifier;
+use crate::okroshka::ir::{IRIdentifier, IRModule, IRTargetLayout, IRTypeEntry};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IRDataStorage {
     Global,
     ThreadLocal
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IRLongDouble {
+    repr: String
+}
+
+impl IRLongDouble {
+    pub fn new<S: Into<String>>(repr: S) -> Option<IRLongDouble> {
+        let repr = repr.into();
+        repr.parse::<f64>().ok()?;
+        Some(IRLongDouble {
+            repr
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.repr
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.repr.parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl fmt::Display for IRLongDouble {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.repr)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum IRDataElement {
     Undefined(u64),
     Integer(i64),
     Float32(f32),
     Float64(f64),
-    LongDouble(f64),
+    LongDouble(IRLongDouble),
     String(Vec<u8>),
     Pointer{ base: String, offset: i64 },
     StringPointer{base: IRIdentifier, offset: i64},
     Raw(Vec<u8>),
-    Aggregate
+    Aggregate,
+    Unknown{class: String, value: serde_json::Value}
 }
 
-#[derive(Debug)]
+impl PartialEq for IRDataElement {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (IRDataElement::Undefined(x), IRDataElement::Undefined(y)) => x == y,
+            (IRDataElement::Integer(x), IRDataElement::Integer(y)) => x == y,
+            (IRDataElement::Float32(x), IRDataElement::Float32(y)) => x.to_bits() == y.to_bits(),
+            (IRDataElement::Float64(x), IRDataElement::Float64(y)) => x.to_bits() == y.to_bits(),
+            (IRDataElement::LongDouble(x), IRDataElement::LongDouble(y)) => x == y,
+            (IRDataElement::String(x), IRDataElement::String(y)) => x == y,
+            (IRDataElement::Pointer { base: base1, offset: offset1 }, IRDataElement::Pointer { base: base2, offset: offset2 })
+                => base1 == base2 && offset1 == offset2,
+            (IRDataElement::StringPointer { base: base1, offset: offset1 }, IRDataElement::StringPointer { base: base2, offset: offset2 })
+                => base1 == base2 && offset1 == offset2,
+            (IRDataElement::Raw(x), IRDataElement::Raw(y)) => x == y,
+            (IRDataElement::Aggregate, IRDataElement::Aggregate) => true,
+            (IRDataElement::Unknown { class: class1, value: value1 }, IRDataElement::Unknown { class: class2, value: value2 })
+                => class1 == class2 && value1 == value2,
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRData {
-    name: String,
+    name: Rc<str>,
     storage: IRDataStorage,
     datatype: IRIdentifier,
     data: Vec<IRDataElement>
 }
 
 impl IRData {
-    pub fn new(name: String, storage: IRDataStorage, datatype: IRIdentifier, data: Vec<IRDataElement>) -> IRData {
+    pub fn new<N: Into<Rc<str>>>(name: N, storage: IRDataStorage, datatype: IRIdentifier, data: Vec<IRDataElement>) -> IRData {
         IRData {
-            name,
+            name: name.into(),
             storage,
             datatype,
             data
@@ -44,10 +99,18 @@ impl IRData {
         &self.name
     }
 
+    pub(crate) fn shared_name(&self) -> Rc<str> {
+        self.name.clone()
+    }
+
     pub fn data_type(&self) -> IRIdentifier {
         self.datatype
     }
 
+    pub(crate) fn set_data_type(&mut self, datatype: IRIdentifier) {
+        self.datatype = datatype;
+    }
+
     pub fn data_storage(&self) -> IRDataStorage {
         self.storage
     }
@@ -56,6 +119,10 @@ impl IRData {
         self.data.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     pub fn at(&self, index: u64) -> Option<&IRDataElement> {
         self.data.get(index as usize)
     }
@@ -63,4 +130,123 @@ impl IRData {
     pub fn elements(&self) -> impl Iterator<Item = &IRDataElement> {
         self.data.iter()
     }
+
+    pub(crate) fn elements_mut(&mut self) -> impl Iterator<Item = &mut IRDataElement> {
+        self.data.iter_mut()
+    }
+
+    pub(crate) fn coalesce_undefined(&mut self) -> usize {
+        let initial_len = self.data.len();
+        let mut data: Vec<IRDataElement> = Vec::with_capacity(initial_len);
+        for element in self.data.drain(..) {
+            match (data.last_mut(), element) {
+                (Some(IRDataElement::Undefined(count)), IRDataElement::Undefined(next_count))
+                    if count.checked_add(next_count).is_some() => *count += next_count,
+                (_, element) => data.push(element)
+            }
+        }
+        self.data = data;
+        initial_len - self.data.len()
+    }
+
+    pub fn total_size(&self, module: &IRModule, target: &IRTargetLayout) -> Option<u64> {
+        let undefined_unit = self.undefined_unit(module, target)?;
+        self.data.iter()
+            .try_fold(0u64, | size, element | size.checked_add(element.size_of(undefined_unit, target)?))
+    }
+
+    pub fn elements_with_offsets(&self, module: &IRModule, target: &IRTargetLayout) -> Option<Vec<(u64, &IRDataElement)>> {
+        let undefined_unit = self.undefined_unit(module, target)?;
+        let mut offset: u64 = 0;
+        self.data.iter()
+            .map(| element | {
+                let element_offset = offset;
+                offset = offset.checked_add(element.size_of(undefined_unit, target)?)?;
+                Some((element_offset, element))
+            })
+            .collect()
+    }
+
+    fn undefined_unit(&self, module: &IRModule, target: &IRTargetLayout) -> Option<u64> {
+        let tp = module.get_type(self.datatype)?;
+        match tp.at(0) {
+            Some(IRTypeEntry::Array { .. }) => tp.size_of(1, target).ok(),
+            Some(IRTypeEntry::Struct { .. }) | Some(IRTypeEntry::Union { .. }) | None => Some(1),
+            Some(_) => tp.size_of(0, target).ok()
+        }
+    }
+}
+
+impl IRDataElement {
+    pub fn undefined_count(&self) -> Option<u64> {
+        match self {
+            IRDataElement::Undefined(count) => Some(*count),
+            _ => None
+        }
+    }
+
+    fn size_of(&self, undefined_unit: u64, target: &IRTargetLayout) -> Option<u64> {
+        match self {
+            IRDataElement::Undefined(count) => count.checked_mul(undefined_unit),
+            IRDataElement::Integer(_) => Some(target.int64.size),
+            IRDataElement::Float32(_) => Some(target.float32.size),
+            IRDataElement::Float64(_) => Some(target.float64.size),
+            IRDataElement::LongDouble(_) => Some(target.long_double.size),
+            IRDataElement::String(content) | IRDataElement::Raw(content) => Some(content.len() as u64),
+            IRDataElement::Pointer { .. } | IRDataElement::StringPointer { .. } => Some(target.word.size),
+            IRDataElement::Aggregate => Some(0),
+            IRDataElement::Unknown { .. } => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::ir::{IRModuleBuilder, IRType};
+
+    fn module_with_type(entries: Vec<IRTypeEntry>) -> IRModule {
+        let mut builder = IRModuleBuilder::new();
+        builder.add_type(IRType::new(0, entries)).unwrap();
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn elements_with_offsets_accumulate_sizes() {
+        let module = module_with_type(vec![IRTypeEntry::Struct { alignment: None, num_of_fields: 0 }]);
+        let data = IRData::new("blob", IRDataStorage::Global, 0, vec![
+            IRDataElement::Integer(1),
+            IRDataElement::Raw(vec![1, 2, 3]),
+            IRDataElement::Undefined(2),
+            IRDataElement::Float32(0.5)
+        ]);
+        let offsets = data.elements_with_offsets(&module, &IRTargetLayout::default()).unwrap()
+            .into_iter()
+            .map(| (offset, _) | offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![0, 8, 11, 13]);
+    }
+
+    #[test]
+    fn elements_with_offsets_scale_undefined_by_data_type() {
+        let module = module_with_type(vec![
+            IRTypeEntry::Array { alignment: None, length: 4 },
+            IRTypeEntry::Int32 { alignment: None }
+        ]);
+        let data = IRData::new("array", IRDataStorage::Global, 0, vec![
+            IRDataElement::Undefined(3),
+            IRDataElement::Integer(1)
+        ]);
+        let offsets = data.elements_with_offsets(&module, &IRTargetLayout::default()).unwrap();
+        assert_eq!(offsets[1].0, 12);
+        let unknown_type = IRData::new("unknown", IRDataStorage::Global, 5, Vec::new());
+        assert!(unknown_type.elements_with_offsets(&module, &IRTargetLayout::default()).is_none());
+    }
+
+    #[test]
+    fn undefined_count_only_for_undefined_runs() {
+        assert_eq!(IRDataElement::Undefined(5).undefined_count(), Some(5));
+        assert_eq!(IRDataElement::Integer(5).undefined_count(), None);
+        assert_eq!(IRDataElement::Raw(vec![0; 5]).undefined_count(), None);
+    }
 }
@@ -1,13 +1,17 @@
+use std::fmt;
+use std::fmt::Write;
 use std::vec::Vec;
+use serde::{Deserialize, Serialize};
 
 use crate::okroshka::ir::core::IRIdentifier;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IRTypeBuiltin {
-    VarargList
+    VarargList,
+    Unknown(String)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IRTypeEntry {
     Struct { alignment: Option<u64>, num_of_fields: usize },
     Array { alignment: Option<u64>, length: u64 },
@@ -29,13 +33,13 @@ pub enum IRTypeEntry {
     Builtin { alignment: Option<u64>, builtin: IRTypeBuiltin }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRType {
     id: IRIdentifier,
     content: Vec<IRTypeEntry>
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct IRTypeRef {
     pub type_id: IRIdentifier,
     pub type_index: usize
@@ -50,6 +54,12 @@ impl IRTypeRef {
     }
 }
 
+impl IRTypeEntry {
+    pub fn is_vararg_list(&self) -> bool {
+        matches!(self, IRTypeEntry::Builtin { builtin: IRTypeBuiltin::VarargList, .. })
+    }
+}
+
 impl IRType {
     pub fn new(id: IRIdentifier, content: Vec<IRTypeEntry>) -> IRType {
         IRType {
@@ -62,15 +72,221 @@ impl IRType {
         self.id
     }
 
+    pub(crate) fn set_identifier(&mut self, id: IRIdentifier) {
+        self.id = id;
+    }
+
     pub fn len(&self) -> usize {
         self.content.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
     pub fn at(&self, index: u64) -> Option<&IRTypeEntry> {
         self.content.get(index as usize)
     }
 
+    pub(crate) fn content(&self) -> &[IRTypeEntry] {
+        &self.content
+    }
+
+    pub fn make_ref(&self, index: usize) -> Option<IRTypeRef> {
+        if index < self.content.len() {
+            Some(IRTypeRef::new(self.id, index))
+        } else {
+            None
+        }
+    }
+
     pub fn type_entries(&self) -> impl Iterator<Item = &IRTypeEntry> {
         self.content.iter()
     }
-}
\ No newline at end of file
+}
+
+impl IRType {
+    pub fn contains_vararg(&self) -> bool {
+        self.content.iter().any(| entry | entry.is_vararg_list())
+    }
+
+    pub fn is_well_formed(&self) -> bool {
+        let mut index = 0;
+        while index < self.content.len() {
+            match self.skip_entry(index) {
+                Some(next_index) => index = next_index,
+                None => return false
+            }
+        }
+        true
+    }
+
+    fn skip_entry(&self, index: usize) -> Option<usize> {
+        match self.content.get(index)? {
+            IRTypeEntry::Struct { num_of_fields, .. } | IRTypeEntry::Union { num_of_fields, .. } => {
+                let mut index = index + 1;
+                for _ in 0..*num_of_fields {
+                    index = self.skip_entry(index)?;
+                }
+                Some(index)
+            },
+            IRTypeEntry::Array { .. } => self.skip_entry(index + 1),
+            _ => Some(index + 1)
+        }
+    }
+
+    fn format_entry(&self, out: &mut String, index: usize) -> Option<usize> {
+        let (alignment, next_index) = match self.content.get(index)? {
+            IRTypeEntry::Struct { alignment, num_of_fields } => {
+                out.push_str("struct ");
+                (alignment, self.format_fields(out, index + 1, *num_of_fields)?)
+            },
+            IRTypeEntry::Union { alignment, num_of_fields } => {
+                out.push_str("union ");
+                (alignment, self.format_fields(out, index + 1, *num_of_fields)?)
+            },
+            IRTypeEntry::Array { alignment, length } => {
+                write!(out, "array[{}] of ", length).ok()?;
+                (alignment, self.format_entry(out, index + 1)?)
+            },
+            IRTypeEntry::Int8 { alignment } => { out.push_str("int8"); (alignment, index + 1) },
+            IRTypeEntry::Int16 { alignment } => { out.push_str("int16"); (alignment, index + 1) },
+            IRTypeEntry::Int32 { alignment } => { out.push_str("int32"); (alignment, index + 1) },
+            IRTypeEntry::Int64 { alignment } => { out.push_str("int64"); (alignment, index + 1) },
+            IRTypeEntry::Float32 { alignment } => { out.push_str("float"); (alignment, index + 1) },
+            IRTypeEntry::Float64 { alignment } => { out.push_str("double"); (alignment, index + 1) },
+            IRTypeEntry::LongDouble { alignment } => { out.push_str("long_double"); (alignment, index + 1) },
+            IRTypeEntry::Bool { alignment } => { out.push_str("bool"); (alignment, index + 1) },
+            IRTypeEntry::Char { alignment } => { out.push_str("char"); (alignment, index + 1) },
+            IRTypeEntry::Short { alignment } => { out.push_str("short"); (alignment, index + 1) },
+            IRTypeEntry::Int { alignment } => { out.push_str("int"); (alignment, index + 1) },
+            IRTypeEntry::Long { alignment } => { out.push_str("long"); (alignment, index + 1) },
+            IRTypeEntry::Word { alignment } => { out.push_str("word"); (alignment, index + 1) },
+            IRTypeEntry::Bits { alignment, width } => {
+                write!(out, "bits({})", width).ok()?;
+                (alignment, index + 1)
+            },
+            IRTypeEntry::Builtin { alignment, builtin: IRTypeBuiltin::VarargList } => {
+                out.push_str("vararg");
+                (alignment, index + 1)
+            },
+            IRTypeEntry::Builtin { alignment, builtin: IRTypeBuiltin::Unknown(class) } => {
+                write!(out, "builtin({})", class).ok()?;
+                (alignment, index + 1)
+            }
+        };
+        if let Some(align) = alignment {
+            write!(out, " align({})", align).ok()?;
+        }
+        Some(next_index)
+    }
+
+    fn format_fields(&self, out: &mut String, mut index: usize, num_of_fields: usize) -> Option<usize> {
+        out.push('{');
+        for field in 0..num_of_fields {
+            out.push_str(if field > 0 { "; " } else { " " });
+            index = self.format_entry(out, index)?;
+        }
+        out.push_str(if num_of_fields > 0 { " }" } else { "}" });
+        Some(index)
+    }
+}
+
+impl fmt::Display for IRType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        let mut index = 0;
+        while index < self.content.len() {
+            if index > 0 {
+                out.push_str(", ");
+            }
+            match self.format_entry(&mut out, index) {
+                Some(next_index) => index = next_index,
+                None => return write!(f, "<malformed>")
+            }
+        }
+        write!(f, "{}", out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_renders_struct_with_array() {
+        let tp = IRType::new(0, vec![
+            IRTypeEntry::Struct { alignment: None, num_of_fields: 3 },
+            IRTypeEntry::Int32 { alignment: None },
+            IRTypeEntry::Int32 { alignment: Some(8) },
+            IRTypeEntry::Array { alignment: None, length: 4 },
+            IRTypeEntry::Int8 { alignment: None }
+        ]);
+        assert_eq!(tp.to_string(), "struct { int32; int32 align(8); array[4] of int8 }");
+    }
+
+    #[test]
+    fn display_degrades_on_malformed_type() {
+        let tp = IRType::new(0, vec![
+            IRTypeEntry::Struct { alignment: None, num_of_fields: 2 },
+            IRTypeEntry::Int32 { alignment: None }
+        ]);
+        assert_eq!(tp.to_string(), "<malformed>");
+    }
+
+    #[test]
+    fn equal_type_refs_deduplicate() {
+        let refs = [IRTypeRef::new(1, 2), IRTypeRef::new(1, 2), IRTypeRef::new(1, 3)]
+            .into_iter()
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(refs.len(), 2);
+        assert!(refs.contains(&IRTypeRef::new(1, 3)));
+    }
+
+    #[test]
+    fn well_formed_checks_aggregate_fields() {
+        let well_formed = IRType::new(0, vec![
+            IRTypeEntry::Struct { alignment: None, num_of_fields: 2 },
+            IRTypeEntry::Array { alignment: None, length: 2 },
+            IRTypeEntry::Int8 { alignment: None },
+            IRTypeEntry::Int32 { alignment: None }
+        ]);
+        assert!(well_formed.is_well_formed());
+        let missing_field = IRType::new(1, vec![
+            IRTypeEntry::Struct { alignment: None, num_of_fields: 2 },
+            IRTypeEntry::Int32 { alignment: None }
+        ]);
+        assert!(!missing_field.is_well_formed());
+        let missing_element = IRType::new(2, vec![IRTypeEntry::Array { alignment: None, length: 2 }]);
+        assert!(!missing_element.is_well_formed());
+    }
+
+    #[test]
+    fn vararg_helpers_detect_vararg_builtin() {
+        let vararg = IRTypeEntry::Builtin { alignment: None, builtin: IRTypeBuiltin::VarargList };
+        assert!(vararg.is_vararg_list());
+        assert!(!IRTypeEntry::Builtin { alignment: None, builtin: IRTypeBuiltin::Unknown("other".to_owned()) }.is_vararg_list());
+        assert!(!IRTypeEntry::Int64 { alignment: None }.is_vararg_list());
+
+        let tp = IRType::new(0, vec![
+            IRTypeEntry::Struct { alignment: None, num_of_fields: 2 },
+            IRTypeEntry::Int32 { alignment: None },
+            vararg
+        ]);
+        assert!(tp.contains_vararg());
+        assert!(!IRType::new(1, vec![IRTypeEntry::Int32 { alignment: None }]).contains_vararg());
+        assert!(!IRType::new(2, Vec::new()).contains_vararg());
+    }
+
+    #[test]
+    fn make_ref_checks_index_range() {
+        let tp = IRType::new(5, vec![
+            IRTypeEntry::Int8 { alignment: None },
+            IRTypeEntry::Int64 { alignment: None }
+        ]);
+        assert_eq!(tp.make_ref(0), Some(IRTypeRef::new(5, 0)));
+        assert_eq!(tp.make_ref(1), Some(IRTypeRef::new(5, 1)));
+        assert_eq!(tp.make_ref(2), None);
+        assert_eq!(IRType::new(6, Vec::new()).make_ref(0), None);
+    }
+}
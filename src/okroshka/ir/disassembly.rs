@@ -0,0 +1,214 @@
+use std::fmt;
+use std::fmt::Write;
+
+use crate::okroshka::ir::{
+    IRModule,
+    IRSymbol,
+    IRSymbolVisibility,
+    IRDataStorage,
+    IRDataElement,
+    IRStringLiteralContent,
+    IRInlineAssemblyParameterClass,
+    IRInlineAssemblyParameterConstraint
+};
+
+fn disassemble_symbol(w: &mut impl Write, kind: &str, sym: &IRSymbol) -> fmt::Result {
+    write!(w, "{}", kind)?;
+    if sym.is_thread_local() {
+        write!(w, " thread_local")?;
+    }
+    let attributes = sym.attributes();
+    if attributes.weak {
+        write!(w, " weak")?;
+    }
+    match attributes.visibility {
+        IRSymbolVisibility::Default => (),
+        IRSymbolVisibility::Hidden => write!(w, " hidden")?,
+        IRSymbolVisibility::Protected => write!(w, " protected")?
+    };
+    writeln!(w, " {}", sym.name())
+}
+
+impl IRModule {
+    pub fn disassemble(&self, w: &mut impl Write) -> fmt::Result {
+        let mut globals = self.globals().collect::<Vec<_>>();
+        globals.sort_by_key(| sym | sym.name());
+        for sym in globals {
+            disassemble_symbol(w, "global", sym)?;
+        }
+
+        let mut externals = self.externals().collect::<Vec<_>>();
+        externals.sort_by_key(| sym | sym.name());
+        for sym in externals {
+            disassemble_symbol(w, "external", sym)?;
+        }
+
+        let mut types = self.types().collect::<Vec<_>>();
+        types.sort_by_key(| tp | tp.identifier());
+        for tp in types {
+            writeln!(w)?;
+            writeln!(w, "type {}: {}", tp.identifier(), tp)?;
+        }
+
+        let mut decls = self.function_declarations().collect::<Vec<_>>();
+        decls.sort_by_key(| decl | decl.identifier());
+        for decl in decls {
+            writeln!(w)?;
+            writeln!(w, "declaration {} {}(params: {}, vararg: {}, returns: {})",
+                decl.identifier(),
+                decl.name().unwrap_or("<anonymous>"),
+                decl.params_type(),
+                decl.params_vararg(),
+                decl.return_type())?;
+        }
+
+        let mut functions = self.functions().collect::<Vec<_>>();
+        functions.sort_by_key(| func | func.name());
+        for func in functions {
+            writeln!(w)?;
+            writeln!(w, "function {} (declaration {}, locals {})", func.name(), func.declaration_id(), func.locals_type())?;
+            for (index, instr) in func.body().code().enumerate() {
+                writeln!(w, "    {:>4}: {}", index, instr)?;
+            }
+        }
+
+        let mut data = self.data().collect::<Vec<_>>();
+        data.sort_by_key(| data | data.name());
+        for data in data {
+            writeln!(w)?;
+            let storage = match data.data_storage() {
+                IRDataStorage::Global => "global",
+                IRDataStorage::ThreadLocal => "thread_local"
+            };
+            writeln!(w, "data {} ({}, type {})", data.name(), storage, data.data_type())?;
+            for (index, element) in data.elements().enumerate() {
+                write!(w, "    {:>4}: ", index)?;
+                match element {
+                    IRDataElement::Undefined(count) => writeln!(w, "undefined {}", count)?,
+                    IRDataElement::Integer(x) => writeln!(w, "integer {}", x)?,
+                    IRDataElement::Float32(x) => writeln!(w, "float32 {}", x)?,
+                    IRDataElement::Float64(x) => writeln!(w, "float64 {}", x)?,
+                    IRDataElement::LongDouble(x) => writeln!(w, "long_double {}", x)?,
+                    IRDataElement::String(content) => writeln!(w, "string {:?}", String::from_utf8_lossy(content))?,
+                    IRDataElement::Pointer { base, offset } => writeln!(w, "pointer {}{:+}", base, offset)?,
+                    IRDataElement::StringPointer { base, offset } => writeln!(w, "string_pointer {}{:+}", base, offset)?,
+                    IRDataElement::Raw(content) => writeln!(w, "raw {:?}", content)?,
+                    IRDataElement::Aggregate => writeln!(w, "aggregate")?,
+                    IRDataElement::Unknown { class, value } => writeln!(w, "unknown {} {}", class, value)?
+                };
+            }
+        }
+
+        let mut literals = self.string_literals().collect::<Vec<_>>();
+        literals.sort_by_key(| literal | literal.identifier());
+        for literal in literals {
+            writeln!(w)?;
+            write!(w, "string {}{} ", literal.identifier(), if literal.is_public() { " public" } else { "" })?;
+            match literal.content() {
+                IRStringLiteralContent::Multibyte(content) => writeln!(w, "multibyte {:?}", String::from_utf8_lossy(content))?,
+                IRStringLiteralContent::Unicode16(content) => writeln!(w, "unicode16 {:?}", content)?,
+                IRStringLiteralContent::Unicode32(content) => writeln!(w, "unicode32 {:?}", content)?
+            };
+        }
+
+        let mut inline_asm = self.inline_assembly().collect::<Vec<_>>();
+        inline_asm.sort_by_key(| inline_asm | inline_asm.identifier());
+        for inline_asm in inline_asm {
+            writeln!(w)?;
+            writeln!(w, "inline_asm {}{}", inline_asm.identifier(), if inline_asm.is_global() { " global" } else { "" })?;
+            writeln!(w, "    template {:?}", inline_asm.template())?;
+
+            let mut params = inline_asm.parameters().collect::<Vec<_>>();
+            params.sort_by_key(| param | param.identifier());
+            for param in params {
+                write!(w, "    parameter {} [{}] ", param.identifier(), param.aliases().collect::<Vec<_>>().join(", "))?;
+                match param.klass() {
+                    IRInlineAssemblyParameterClass::ImmediateConstant(typeref, value)
+                        => write!(w, "immediate {{type:{}, index:{}}} {}", typeref.type_id, typeref.type_index, value)?,
+                    IRInlineAssemblyParameterClass::ImmediateIdentifierBased(typeref, base, value)
+                        => write!(w, "immediate {{type:{}, index:{}}} {}{:+}", typeref.type_id, typeref.type_index, base, value)?,
+                    IRInlineAssemblyParameterClass::ImmediateLiteralBased(typeref, literal, value)
+                        => write!(w, "immediate {{type:{}, index:{}}} string {}{:+}", typeref.type_id, typeref.type_index, literal, value)?,
+                    IRInlineAssemblyParameterClass::Read(typeref, index)
+                        => write!(w, "read {{type:{}, index:{}}} from {}", typeref.type_id, typeref.type_index, index)?,
+                    IRInlineAssemblyParameterClass::Load(typeref, index)
+                        => write!(w, "load {{type:{}, index:{}}} from {}", typeref.type_id, typeref.type_index, index)?,
+                    IRInlineAssemblyParameterClass::Store(typeref, index)
+                        => write!(w, "store {{type:{}, index:{}}} to {}", typeref.type_id, typeref.type_index, index)?,
+                    IRInlineAssemblyParameterClass::LoadStore(typeref, index)
+                        => write!(w, "load_store {{type:{}, index:{}}} from_to {}", typeref.type_id, typeref.type_index, index)?,
+                    IRInlineAssemblyParameterClass::ReadStore(from_typeref, from_index, to_typeref, to_index)
+                        => write!(w, "read_store {{type:{}, index:{}}} from {} {{type:{}, index:{}}} to {}",
+                            from_typeref.type_id, from_typeref.type_index, from_index,
+                            to_typeref.type_id, to_typeref.type_index, to_index)?
+                };
+                match param.constraint() {
+                    IRInlineAssemblyParameterConstraint::None => writeln!(w)?,
+                    IRInlineAssemblyParameterConstraint::Register => writeln!(w, " register")?,
+                    IRInlineAssemblyParameterConstraint::Memory => writeln!(w, " memory")?,
+                    IRInlineAssemblyParameterConstraint::RegisterMemory => writeln!(w, " register_memory")?
+                };
+            }
+
+            let mut clobbers = inline_asm.clobbers().collect::<Vec<_>>();
+            clobbers.sort();
+            if !clobbers.is_empty() {
+                writeln!(w, "    clobbers {}", clobbers.join(", "))?;
+            }
+
+            let mut jump_targets = inline_asm.jump_targets().collect::<Vec<_>>();
+            jump_targets.sort_by_key(| target | target.identifier());
+            for target in jump_targets {
+                writeln!(w, "    jump_target {} [{}] {}+{}",
+                    target.identifier(),
+                    target.aliases().collect::<Vec<_>>().join(", "),
+                    target.target_function(),
+                    target.target_function_offset())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::okroshka::testing::load_module;
+
+    #[test]
+    fn disassemble_small_module() {
+        let module = load_module(r#"{
+            "globals": [{"identifier": "main", "type": "global"}],
+            "externals": [],
+            "types": [{"identifier": 0, "type": [{"type": "struct", "fields": []}]}],
+            "function_declarations": [{"identifier": 0, "name": "main", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "main", "locals": 0, "body": [
+                {"opcode": "pushstring", "arg": 0},
+                {"opcode": "ret"}
+            ]}],
+            "data": [{"identifier": "value", "storage": "global", "type": 0, "value": [{"class": "integer", "value": 1}]}],
+            "string_literals": [{"id": 0, "public": false, "type": "multibyte", "literal": "hi"}],
+            "inline_assembly": [{"identifier": 0, "global": true, "template": "nop", "parameters": [], "clobbers": [], "jump_targets": []}]
+        }"#);
+        let mut listing = String::new();
+        module.disassemble(&mut listing).unwrap();
+        assert_eq!(listing, concat!(
+            "global main\n",
+            "\n",
+            "type 0: struct {}\n",
+            "\n",
+            "declaration 0 main(params: 0, vararg: false, returns: 0)\n",
+            "\n",
+            "function main (declaration 0, locals 0)\n",
+            "       0: pushstring 0\n",
+            "       1: ret\n",
+            "\n",
+            "data value (global, type 0)\n",
+            "       0: integer 1\n",
+            "\n",
+            "string 0 multibyte \"hi\"\n",
+            "\n",
+            "inline_asm 0 global\n",
+            "    template \"nop\"\n"));
+    }
+}
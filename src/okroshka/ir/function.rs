@@ -1,6 +1,11 @@
-use crate::okroshka::ir::{IRIdentifier, IRBlock};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::fmt::Write;
+use std::rc::Rc;
 
-#[derive(Debug)]
+use crate::okroshka::ir::{IRIdentifier, IRTypeRef, IRBlock, IRModule, IRInstruction, IRInstructionArgument};
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRFunctionDeclaration {
     id: IRIdentifier,
     name: Option<String>,
@@ -9,9 +14,16 @@ pub struct IRFunctionDeclaration {
     result: IRIdentifier
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IRSourceLocation {
+    pub file: String,
+    pub line: u64,
+    pub column: u64
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRFunction {
-    name: String,
+    name: Rc<str>,
     declaration: IRIdentifier,
     locals: IRIdentifier,
     body: IRBlock
@@ -33,6 +45,10 @@ impl IRFunctionDeclaration {
         self.id
     }
 
+    pub(crate) fn set_identifier(&mut self, id: IRIdentifier) {
+        self.id = id;
+    }
+
     pub fn name(&self) -> Option<&str> {
         match &self.name {
             Some(x) => Some(x.as_str()),
@@ -40,10 +56,18 @@ impl IRFunctionDeclaration {
         }
     }
 
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
     pub fn params_type(&self) -> IRIdentifier {
         self.params
     }
 
+    pub(crate) fn set_params_type(&mut self, params: IRIdentifier) {
+        self.params = params;
+    }
+
     pub fn params_vararg(&self) -> bool {
         self.vararg
     }
@@ -51,12 +75,16 @@ impl IRFunctionDeclaration {
     pub fn return_type(&self) -> IRIdentifier {
         self.result
     }
+
+    pub(crate) fn set_return_type(&mut self, result: IRIdentifier) {
+        self.result = result;
+    }
 }
 
 impl IRFunction {
-    pub fn new(name: String, declaration: IRIdentifier, locals: IRIdentifier, body: IRBlock) -> IRFunction {
+    pub fn new<N: Into<Rc<str>>>(name: N, declaration: IRIdentifier, locals: IRIdentifier, body: IRBlock) -> IRFunction {
         IRFunction {
-            name,
+            name: name.into(),
             declaration,
             locals,
             body
@@ -67,15 +95,226 @@ impl IRFunction {
         &self.name
     }
 
+    pub(crate) fn shared_name(&self) -> Rc<str> {
+        self.name.clone()
+    }
+
+    pub(crate) fn set_name(&mut self, name: Rc<str>) {
+        self.name = name;
+    }
+
     pub fn declaration_id(&self) -> IRIdentifier {
         self.declaration
     }
 
+    pub(crate) fn set_declaration_id(&mut self, declaration: IRIdentifier) {
+        self.declaration = declaration;
+    }
+
     pub fn locals_type(&self) -> IRIdentifier {
         self.locals
     }
 
+    pub(crate) fn set_locals_type(&mut self, locals: IRIdentifier) {
+        self.locals = locals;
+    }
+
     pub fn body(&self) -> &IRBlock {
         &self.body
     }
+
+    pub fn body_mut(&mut self) -> &mut IRBlock {
+        &mut self.body
+    }
+
+    pub fn instruction_count(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    pub fn instruction_at(&self, index: usize) -> Option<&IRInstruction> {
+        self.body.at(index)
+    }
+
+    pub fn source_location(&self, instr_index: usize) -> Option<IRSourceLocation> {
+        let location = self.body.metadata_at(instr_index)?.get("source_location")?;
+        Some(IRSourceLocation {
+            file: location.get("file")?.as_str()?.to_owned(),
+            line: location.get("line")?.as_u64()?,
+            column: location.get("column")?.as_u64()?
+        })
+    }
+
+    pub fn type_refs(&self) -> impl Iterator<Item = IRTypeRef> + '_ {
+        self.body.code()
+            .filter_map(| instr | match instr.argument() {
+                IRInstructionArgument::TypeRef(typeref) => Some(typeref),
+                _ => None
+            })
+    }
+
+    pub fn constant_push_runs(&self) -> Vec<Range<usize>> {
+        let code = self.body.code().collect::<Vec<_>>();
+        let blocks = self.body.basic_blocks();
+        code.iter()
+            .enumerate()
+            .filter_map(| (index, instr) | {
+                let arity = instr.fold_arity()?;
+                let start = index.checked_sub(arity)?;
+                let same_block = blocks.iter()
+                    .any(| block | block.start <= start && index < block.end);
+                if same_block && code[start..index].iter().all(| operand | operand.is_constant_push()) {
+                    Some(start..index + 1)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_dot(&self, module: &IRModule) -> String {
+        let body = self.body();
+        let mut leaders = body.basic_blocks()
+            .into_iter()
+            .map(| block | block.start)
+            .collect::<BTreeSet<_>>();
+        for inline_asm in module.inline_assembly() {
+            for jump_target in inline_asm.jump_targets() {
+                if jump_target.target_function() == self.name() && jump_target.target_function_offset() < body.len() {
+                    leaders.insert(jump_target.target_function_offset());
+                }
+            }
+        }
+        let leaders = leaders.into_iter().collect::<Vec<_>>();
+        let block_of = | index: usize | leaders.partition_point(| &leader | leader <= index) - 1;
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph {:?} {{", self.name()).unwrap();
+        writeln!(dot, "    node [shape=box];").unwrap();
+        for (block, &begin) in leaders.iter().enumerate() {
+            let end = leaders.get(block + 1).copied().unwrap_or(body.len());
+            writeln!(dot, "    block{} [label=\"{}..{}\"];", block, begin, end).unwrap();
+        }
+        for block in 0..leaders.len() {
+            let end = leaders.get(block + 1).copied().unwrap_or(body.len());
+            let last_instr = match body.at(end - 1) {
+                Some(instr) => instr,
+                None => continue
+            };
+            match last_instr.argument() {
+                IRInstructionArgument::CodeRef(target) if last_instr.is_branch() && target < body.len()
+                    => if last_instr.is_terminator() {
+                    writeln!(dot, "    block{} -> block{};", block, block_of(target)).unwrap();
+                } else {
+                    writeln!(dot, "    block{} -> block{} [label=\"branch\"];", block, block_of(target)).unwrap();
+                },
+                _ => ()
+            }
+            if !last_instr.is_terminator() && end < body.len() {
+                writeln!(dot, "    block{} -> block{};", block, block + 1).unwrap();
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::testing::load_module;
+
+    #[test]
+    fn instruction_accessors_delegate_to_body() {
+        let empty = IRFunction::new("empty", 0, 0, IRBlock::new(Vec::new()));
+        assert!(empty.is_empty());
+        assert_eq!(empty.instruction_count(), 0);
+        assert_eq!(empty.instruction_at(0), None);
+        let func = IRFunction::new("func", 0, 0, IRBlock::new(vec![IRInstruction::pushi64(1), IRInstruction::ret]));
+        assert!(!func.is_empty());
+        assert_eq!(func.instruction_count(), 2);
+        assert_eq!(func.instruction_at(1), Some(&IRInstruction::ret));
+        assert_eq!(func.instruction_at(2), None);
+    }
+
+    #[test]
+    fn type_refs_collect_every_type_reference() {
+        let func = IRFunction::new("func", 0, 0, IRBlock::new(vec![
+            IRInstruction::getlocal(IRTypeRef::new(1, 0)),
+            IRInstruction::pushi64(1),
+            IRInstruction::getlocal(IRTypeRef::new(2, 3)),
+            IRInstruction::ret
+        ]));
+        assert_eq!(func.type_refs().collect::<Vec<_>>(), vec![IRTypeRef::new(1, 0), IRTypeRef::new(2, 3)]);
+    }
+
+    #[test]
+    fn source_location_reads_instruction_metadata() {
+        let module = load_module(r#"{
+            "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "main", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "main", "locals": 0, "body": [
+                {"opcode": "push", "arg": 1},
+                {"opcode": "push", "arg": 2, "metadata": {"source_location": {"file": "main.c", "line": 3, "column": 14}}},
+                {"opcode": "iadd", "metadata": {"source_location": {"file": "main.c"}}},
+                {"opcode": "ret"}
+            ]}]
+        }"#);
+        let func = module.get_function("main").unwrap();
+        assert_eq!(func.source_location(0), None);
+        assert_eq!(func.source_location(1), Some(IRSourceLocation {
+            file: "main.c".to_owned(),
+            line: 3,
+            column: 14
+        }));
+        assert_eq!(func.source_location(2), None);
+        assert!(func.body().metadata_at(2).is_some());
+        assert_eq!(func.source_location(4), None);
+    }
+
+    #[test]
+    fn constant_push_runs_find_foldable_sequences() {
+        let func = IRFunction::new("func", 0, 0, IRBlock::new(vec![
+            IRInstruction::pushi64(2),
+            IRInstruction::pushi64(3),
+            IRInstruction::iadd,
+            IRInstruction::getlocal(IRTypeRef::new(0, 0)),
+            IRInstruction::pushf64(1.5),
+            IRInstruction::f64add,
+            IRInstruction::pushi64(1),
+            IRInstruction::ineg,
+            IRInstruction::ret
+        ]));
+        assert_eq!(func.constant_push_runs(), vec![0..3, 6..8]);
+        assert!(IRInstruction::pushu64(1).is_constant_push());
+        assert!(!IRInstruction::pushstring(1).is_constant_push());
+        assert_eq!(IRInstruction::isub.fold_arity(), Some(2));
+        assert_eq!(IRInstruction::bnot.fold_arity(), Some(1));
+        assert_eq!(IRInstruction::ret.fold_arity(), None);
+    }
+
+    #[test]
+    fn to_dot_emits_node_per_basic_block() {
+        let module = load_module(r#"{
+            "globals": [], "externals": [],
+            "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "main", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "main", "locals": 0, "body": [
+                {"opcode": "push", "arg": 1},
+                {"opcode": "branch", "arg": 4},
+                {"opcode": "push", "arg": 2},
+                {"opcode": "ret"},
+                {"opcode": "push", "arg": 3},
+                {"opcode": "ret"}
+            ]}],
+            "data": [], "string_literals": [], "inline_assembly": []
+        }"#);
+        let dot = module.get_function("main").unwrap().to_dot(&module);
+        assert_eq!(dot.lines().filter(| line | line.contains("[label=\"") && !line.contains("->")).count(), 3);
+        assert!(dot.contains("block0 -> block2 [label=\"branch\"];"));
+        assert!(dot.contains("block0 -> block1;"));
+    }
 }
@@ -1,10 +1,41 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
 use crate::okroshka::ir::{
-    IRTypeRef
+    IRTypeRef,
+    IRError
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum IRMemoryOrder {
+    Relaxed,
+    Acquire,
+    Release,
+    AcqRel,
+    SeqCst
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct IRInstructionMemFlags {
-    pub volatile: bool
+    pub volatile: bool,
+    pub atomic: Option<IRMemoryOrder>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpcodeArgClass {
+    None,
+    CodeReference,
+    FunctionReference,
+    TypeReference,
+    Integer64,
+    UInteger64,
+    UInteger32,
+    Float64,
+    Float32,
+    Boolean,
+    String,
+    Identifier,
+    MemFlags
 }
 
 #[derive(Debug)]
@@ -24,4 +55,216 @@ pub enum IRInstructionArgument<'a> {
     MemFlags(IRInstructionMemFlags)
 }
 
-include!(concat!(env!("OUT_DIR"), "/opcodes.rs"));
\ No newline at end of file
+#[derive(Debug, Clone, PartialEq)]
+pub enum IRInstructionArgumentOwned {
+    None,
+    Integer(i64),
+    UInteger(u64),
+    UIntegerPair(u32, u32),
+    Boolean(bool),
+    Float64(f64),
+    Float32(f32),
+    String(u64),
+    TypeRef(IRTypeRef),
+    CodeRef(usize),
+    Identifier(String),
+    FunctionRef(u64, Option<String>),
+    MemFlags(IRInstructionMemFlags)
+}
+
+impl<'a> From<IRInstructionArgument<'a>> for IRInstructionArgumentOwned {
+    fn from(argument: IRInstructionArgument<'a>) -> IRInstructionArgumentOwned {
+        match argument {
+            IRInstructionArgument::None => IRInstructionArgumentOwned::None,
+            IRInstructionArgument::Integer(x) => IRInstructionArgumentOwned::Integer(x),
+            IRInstructionArgument::UInteger(x) => IRInstructionArgumentOwned::UInteger(x),
+            IRInstructionArgument::UIntegerPair(x, y) => IRInstructionArgumentOwned::UIntegerPair(x, y),
+            IRInstructionArgument::Boolean(x) => IRInstructionArgumentOwned::Boolean(x),
+            IRInstructionArgument::Float64(x) => IRInstructionArgumentOwned::Float64(x),
+            IRInstructionArgument::Float32(x) => IRInstructionArgumentOwned::Float32(x),
+            IRInstructionArgument::String(x) => IRInstructionArgumentOwned::String(x),
+            IRInstructionArgument::TypeRef(typeref) => IRInstructionArgumentOwned::TypeRef(typeref),
+            IRInstructionArgument::CodeRef(x) => IRInstructionArgumentOwned::CodeRef(x),
+            IRInstructionArgument::Identifier(x) => IRInstructionArgumentOwned::Identifier(x.to_owned()),
+            IRInstructionArgument::FunctionRef(x, name) => IRInstructionArgumentOwned::FunctionRef(x, name.cloned()),
+            IRInstructionArgument::MemFlags(memflags) => IRInstructionArgumentOwned::MemFlags(memflags)
+        }
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/opcodes.rs"));
+
+impl IRInstruction {
+    pub fn argument_owned(&self) -> IRInstructionArgumentOwned {
+        self.argument().into()
+    }
+}
+
+impl fmt::Display for IRInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        match self.argument() {
+            IRInstructionArgument::None => Ok(()),
+            IRInstructionArgument::Integer(x) => write!(f, " {}", x),
+            IRInstructionArgument::UInteger(x) => write!(f, " {}", x),
+            IRInstructionArgument::UIntegerPair(x, y) => write!(f, " {}, {}", x, y),
+            IRInstructionArgument::Boolean(x) => write!(f, " {}", x),
+            IRInstructionArgument::Float64(x) => write!(f, " {}", x),
+            IRInstructionArgument::Float32(x) => write!(f, " {}", x),
+            IRInstructionArgument::String(x) => write!(f, " {}", x),
+            IRInstructionArgument::TypeRef(typeref) => write!(f, " {{type:{}, index:{}}}", typeref.type_id, typeref.type_index),
+            IRInstructionArgument::CodeRef(x) => write!(f, " {}", x),
+            IRInstructionArgument::Identifier(x) => write!(f, " {}", x),
+            IRInstructionArgument::FunctionRef(x, Some(name)) => write!(f, " {} ({})", x, name),
+            IRInstructionArgument::FunctionRef(x, None) => write!(f, " {}", x),
+            IRInstructionArgument::MemFlags(memflags) => {
+                if memflags.volatile {
+                    write!(f, " volatile")?;
+                }
+                match memflags.atomic {
+                    Some(IRMemoryOrder::Relaxed) => write!(f, " relaxed"),
+                    Some(IRMemoryOrder::Acquire) => write!(f, " acquire"),
+                    Some(IRMemoryOrder::Release) => write!(f, " release"),
+                    Some(IRMemoryOrder::AcqRel) => write!(f, " acq_rel"),
+                    Some(IRMemoryOrder::SeqCst) => write!(f, " seq_cst"),
+                    None => Ok(())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::ir::IRBlock;
+
+    #[test]
+    fn display_renders_each_argument_class() {
+        let memflags = IRInstructionMemFlags { volatile: true, atomic: None };
+        let cases = [
+            (IRInstruction::ret, "ret"),
+            (IRInstruction::jmp(4), "jmp 4"),
+            (IRInstruction::invoke(7, Some("foo".to_owned())), "invoke 7 (foo)"),
+            (IRInstruction::invoke(7, None), "invoke 7"),
+            (IRInstruction::getlocal(IRTypeRef::new(3, 1)), "getlocal {type:3, index:1}"),
+            (IRInstruction::pushi64(-42), "push -42"),
+            (IRInstruction::pushu64(42), "pushu 42"),
+            (IRInstruction::extubits(8, 16), "extubits 8, 16"),
+            (IRInstruction::pushf64(1.5), "pushf64 1.5"),
+            (IRInstruction::pushf32(0.25), "pushf32 0.25"),
+            (IRInstruction::alloca(true), "alloca true"),
+            (IRInstruction::pushstring(2), "pushstring 2"),
+            (IRInstruction::getglobal("counter".to_owned()), "getglobal counter"),
+            (IRInstruction::load8u(memflags), "load8u volatile")
+        ];
+        for (instr, expected) in cases {
+            assert_eq!(instr.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn display_renders_atomic_memory_order() {
+        let memflags = IRInstructionMemFlags { volatile: false, atomic: Some(IRMemoryOrder::SeqCst) };
+        assert_eq!(IRInstruction::store64(memflags).to_string(), "store64 seq_cst");
+    }
+
+    #[test]
+    fn control_classification_covers_transfers_and_calls() {
+        assert!(IRInstruction::ret.is_terminator());
+        assert!(!IRInstruction::ret.is_branch());
+        assert!(IRInstruction::jmp(0).is_branch());
+        assert!(IRInstruction::jmp(0).is_terminator());
+        assert!(IRInstruction::branch(0).is_branch());
+        assert!(!IRInstruction::branch(0).is_terminator());
+        assert!(IRInstruction::invoke(0, None).is_call());
+        assert!(!IRInstruction::iadd.is_branch());
+        assert!(!IRInstruction::iadd.is_terminator());
+        assert!(!IRInstruction::iadd.is_call());
+    }
+
+    #[test]
+    fn class_of_mnemonic_matches_argument_class() {
+        assert_eq!(IRInstruction::class_of_mnemonic("invoke"), Some(OpcodeArgClass::FunctionReference));
+        assert_eq!(IRInstruction::class_of_mnemonic("getlocal"), Some(OpcodeArgClass::TypeReference));
+        assert_eq!(IRInstruction::class_of_mnemonic("ret"), Some(OpcodeArgClass::None));
+        assert_eq!(IRInstruction::class_of_mnemonic("frobnicate"), None);
+    }
+
+    #[test]
+    fn all_mnemonics_lists_known_opcodes() {
+        let mnemonics = IRInstruction::all_mnemonics();
+        assert!(!mnemonics.is_empty());
+        assert!(mnemonics.contains(&"iadd"));
+        assert_eq!(IRInstruction::all_opcode_codes().len(), mnemonics.len());
+    }
+
+    #[test]
+    fn code_of_mnemonic_matches_instruction_code() {
+        assert_eq!(IRInstruction::code_of_mnemonic("iadd"), Some(IRInstruction::iadd.code()));
+        assert_eq!(IRInstruction::code_of_mnemonic("push"), Some(IRInstruction::pushi64(0).code()));
+        assert_eq!(IRInstruction::code_of_mnemonic("frobnicate"), None);
+    }
+
+    #[test]
+    fn known_variant_reports_code_and_mnemonic() {
+        let instr = IRInstruction::getglobal("counter".to_owned());
+        assert_eq!(instr.mnemonic(), "getglobal");
+        assert_eq!(instr.code(), IRInstruction::getglobal(String::new()).code());
+        assert_ne!(instr.code(), IRInstruction::getthrlocal("counter".to_owned()).code());
+    }
+
+    #[test]
+    fn from_parts_builds_matching_variant() {
+        assert_eq!(IRInstruction::from_parts("push", IRInstructionArgumentOwned::Integer(5)), Ok(IRInstruction::pushi64(5)));
+        assert_eq!(IRInstruction::from_parts("getlocal", IRInstructionArgumentOwned::TypeRef(IRTypeRef::new(1, 2))),
+            Ok(IRInstruction::getlocal(IRTypeRef::new(1, 2))));
+        assert_eq!(IRInstruction::from_parts("ret", IRInstructionArgumentOwned::None), Ok(IRInstruction::ret));
+        let instr = IRInstruction::invoke(3, Some("foo".to_owned()));
+        assert_eq!(IRInstruction::from_parts(instr.mnemonic(), instr.argument_owned()), Ok(instr));
+    }
+
+    #[test]
+    fn from_parts_rejects_mismatched_argument() {
+        assert!(matches!(IRInstruction::from_parts("getlocal", IRInstructionArgumentOwned::Integer(5)),
+            Err(IRError::InstructionArgumentMismatch(mnemonic)) if mnemonic == "getlocal"));
+        assert!(matches!(IRInstruction::from_parts("ret", IRInstructionArgumentOwned::UInteger(1)),
+            Err(IRError::InstructionArgumentMismatch(_))));
+        assert!(matches!(IRInstruction::from_parts("frobnicate", IRInstructionArgumentOwned::None),
+            Err(IRError::UnknownOpcode(mnemonic)) if mnemonic == "frobnicate"));
+    }
+
+    #[test]
+    fn discriminant_round_trips_through_mnemonic() {
+        let instr = IRInstruction::getlocal(IRTypeRef::new(1, 0));
+        let discriminant = instr.discriminant();
+        let mnemonic = IRInstruction::mnemonic_of_discriminant(discriminant).unwrap();
+        assert_eq!(mnemonic, instr.mnemonic());
+        assert_eq!(IRInstruction::code_of_discriminant(discriminant), Some(instr.code()));
+        assert_eq!(IRInstruction::from_parts(mnemonic, instr.argument_owned()), Ok(instr));
+        let count = IRInstruction::all_mnemonics().len() as u32;
+        assert_eq!(IRInstruction::mnemonic_of_discriminant(count), None);
+        assert_eq!(IRInstruction::code_of_discriminant(count), None);
+    }
+
+    #[test]
+    fn argument_owned_outlives_instruction() {
+        let arguments = {
+            let body = IRBlock::new(vec![
+                IRInstruction::getglobal("counter".to_owned()),
+                IRInstruction::invoke(1, Some("printf".to_owned())),
+                IRInstruction::pushi64(3),
+                IRInstruction::ret
+            ]);
+            body.code()
+                .map(| instr | instr.argument_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(arguments, vec![
+            IRInstructionArgumentOwned::Identifier("counter".to_owned()),
+            IRInstructionArgumentOwned::FunctionRef(1, Some("printf".to_owned())),
+            IRInstructionArgumentOwned::Integer(3),
+            IRInstructionArgumentOwned::None
+        ]);
+    }
+}
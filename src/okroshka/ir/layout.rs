@@ -0,0 +1,208 @@
+use crate::okroshka::ir::{
+    IRError,
+    IRType,
+    IRTypeEntry,
+    IRTypeBuiltin
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endianness {
+    Little,
+    Big
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IRTypeLayout {
+    pub size: u64,
+    pub alignment: u64
+}
+
+impl IRTypeLayout {
+    pub fn new(size: u64, alignment: u64) -> IRTypeLayout {
+        IRTypeLayout {
+            size,
+            alignment
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IRTargetLayout {
+    pub int8: IRTypeLayout,
+    pub int16: IRTypeLayout,
+    pub int32: IRTypeLayout,
+    pub int64: IRTypeLayout,
+    pub float32: IRTypeLayout,
+    pub float64: IRTypeLayout,
+    pub long_double: IRTypeLayout,
+    pub bool: IRTypeLayout,
+    pub char: IRTypeLayout,
+    pub short: IRTypeLayout,
+    pub int: IRTypeLayout,
+    pub long: IRTypeLayout,
+    pub word: IRTypeLayout,
+    pub vararg_list: IRTypeLayout
+}
+
+impl IRTargetLayout {
+    pub fn x86_64_sysv() -> IRTargetLayout {
+        IRTargetLayout {
+            int8: IRTypeLayout::new(1, 1),
+            int16: IRTypeLayout::new(2, 2),
+            int32: IRTypeLayout::new(4, 4),
+            int64: IRTypeLayout::new(8, 8),
+            float32: IRTypeLayout::new(4, 4),
+            float64: IRTypeLayout::new(8, 8),
+            long_double: IRTypeLayout::new(16, 16),
+            bool: IRTypeLayout::new(1, 1),
+            char: IRTypeLayout::new(1, 1),
+            short: IRTypeLayout::new(2, 2),
+            int: IRTypeLayout::new(4, 4),
+            long: IRTypeLayout::new(8, 8),
+            word: IRTypeLayout::new(8, 8),
+            vararg_list: IRTypeLayout::new(24, 8)
+        }
+    }
+}
+
+impl Default for IRTargetLayout {
+    fn default() -> IRTargetLayout {
+        IRTargetLayout::x86_64_sysv()
+    }
+}
+
+fn align_to(offset: u64, alignment: u64) -> Option<u64> {
+    if alignment <= 1 {
+        Some(offset)
+    } else {
+        offset.checked_add(alignment - 1)
+            .map(| x | x / alignment * alignment)
+    }
+}
+
+impl IRType {
+    pub fn layout_of(&self, index: usize, target: &IRTargetLayout) -> Result<IRTypeLayout, IRError> {
+        self.entry_layout(index, target)
+            .map(| (layout, _) | layout)
+    }
+
+    pub fn size_of(&self, index: usize, target: &IRTargetLayout) -> Result<u64, IRError> {
+        Ok(self.layout_of(index, target)?.size)
+    }
+
+    pub fn field_offsets(&self, target: &IRTargetLayout) -> Option<Vec<u64>> {
+        let (num_of_fields, union) = match self.at(0)? {
+            IRTypeEntry::Struct { num_of_fields, .. } => (*num_of_fields, false),
+            IRTypeEntry::Union { num_of_fields, .. } => (*num_of_fields, true),
+            _ => return None
+        };
+        let mut offsets = Vec::with_capacity(num_of_fields.min(self.len()));
+        self.aggregate_layout(1, num_of_fields, union, target, Some(&mut offsets)).ok()?;
+        Some(offsets)
+    }
+
+    fn layout_overflow(&self, index: usize) -> IRError {
+        IRError::TypeLayoutOverflow { type_id: self.identifier(), index }
+    }
+
+    fn entry_layout(&self, index: usize, target: &IRTargetLayout) -> Result<(IRTypeLayout, usize), IRError> {
+        let entry = self.at(index as u64)
+            .ok_or(IRError::TypeIndexOutOfRange { type_id: self.identifier(), index, len: self.len() })?;
+        let (alignment, layout, next_index) = match entry {
+            IRTypeEntry::Struct { alignment, num_of_fields } => {
+                let (layout, next_index) = self.aggregate_layout(index + 1, *num_of_fields, false, target, None)?;
+                (alignment, layout, next_index)
+            },
+            IRTypeEntry::Union { alignment, num_of_fields } => {
+                let (layout, next_index) = self.aggregate_layout(index + 1, *num_of_fields, true, target, None)?;
+                (alignment, layout, next_index)
+            },
+            IRTypeEntry::Array { alignment, length } => {
+                let (element, next_index) = self.entry_layout(index + 1, target)?;
+                let size = element.size.checked_mul(*length)
+                    .ok_or_else(|| self.layout_overflow(index))?;
+                (alignment, IRTypeLayout::new(size, element.alignment), next_index)
+            },
+            IRTypeEntry::Int8 { alignment } => (alignment, target.int8, index + 1),
+            IRTypeEntry::Int16 { alignment } => (alignment, target.int16, index + 1),
+            IRTypeEntry::Int32 { alignment } => (alignment, target.int32, index + 1),
+            IRTypeEntry::Int64 { alignment } => (alignment, target.int64, index + 1),
+            IRTypeEntry::Float32 { alignment } => (alignment, target.float32, index + 1),
+            IRTypeEntry::Float64 { alignment } => (alignment, target.float64, index + 1),
+            IRTypeEntry::LongDouble { alignment } => (alignment, target.long_double, index + 1),
+            IRTypeEntry::Bool { alignment } => (alignment, target.bool, index + 1),
+            IRTypeEntry::Char { alignment } => (alignment, target.char, index + 1),
+            IRTypeEntry::Short { alignment } => (alignment, target.short, index + 1),
+            IRTypeEntry::Int { alignment } => (alignment, target.int, index + 1),
+            IRTypeEntry::Long { alignment } => (alignment, target.long, index + 1),
+            IRTypeEntry::Word { alignment } => (alignment, target.word, index + 1),
+            IRTypeEntry::Bits { alignment, width } => {
+                let size = width / 8 + if width % 8 != 0 { 1 } else { 0 };
+                (alignment, IRTypeLayout::new(size, size.next_power_of_two().min(8)), index + 1)
+            },
+            IRTypeEntry::Builtin { alignment, builtin: IRTypeBuiltin::VarargList } => (alignment, target.vararg_list, index + 1),
+            IRTypeEntry::Builtin { builtin: IRTypeBuiltin::Unknown(_), .. }
+                => Err(IRError::TypeLayoutUnavailable { type_id: self.identifier(), index })?
+        };
+        match alignment {
+            Some(alignment) if *alignment > 0 => Ok((IRTypeLayout::new(layout.size, *alignment), next_index)),
+            _ => Ok((layout, next_index))
+        }
+    }
+
+    fn aggregate_layout(&self, mut index: usize, num_of_fields: usize, union: bool, target: &IRTargetLayout,
+        mut offsets: Option<&mut Vec<u64>>) -> Result<(IRTypeLayout, usize), IRError> {
+        let mut size: u64 = 0;
+        let mut alignment: u64 = 1;
+        for _ in 0..num_of_fields {
+            let (field, next_index) = self.entry_layout(index, target)?;
+            let offset = if union {
+                0
+            } else {
+                align_to(size, field.alignment)
+                    .ok_or_else(|| self.layout_overflow(index))?
+            };
+            if let Some(offsets) = offsets.as_mut() {
+                offsets.push(offset);
+            }
+            size = size.max(offset.checked_add(field.size)
+                .ok_or_else(|| self.layout_overflow(index))?);
+            alignment = alignment.max(field.alignment);
+            index = next_index;
+        }
+        let size = align_to(size, alignment)
+            .ok_or_else(|| self.layout_overflow(index))?;
+        Ok((IRTypeLayout::new(size, alignment), index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_of_reports_array_overflow() {
+        let tp = IRType::new(3, vec![
+            IRTypeEntry::Array { alignment: None, length: u64::MAX },
+            IRTypeEntry::Int64 { alignment: None }
+        ]);
+        assert!(matches!(tp.size_of(0, &IRTargetLayout::default()), Err(IRError::TypeLayoutOverflow { type_id: 3, index: 0 })));
+    }
+
+    #[test]
+    fn field_offsets_account_for_padding() {
+        let target = IRTargetLayout::default();
+        let fields = vec![IRTypeEntry::Int8 { alignment: None }, IRTypeEntry::Int32 { alignment: None }];
+        let structure = IRType::new(0, [vec![IRTypeEntry::Struct { alignment: None, num_of_fields: 2 }], fields.clone()].concat());
+        assert_eq!(structure.field_offsets(&target), Some(vec![0, 4]));
+        let union = IRType::new(1, [vec![IRTypeEntry::Union { alignment: None, num_of_fields: 2 }], fields].concat());
+        assert_eq!(union.field_offsets(&target), Some(vec![0, 0]));
+        assert_eq!(IRType::new(2, vec![IRTypeEntry::Int32 { alignment: None }]).field_offsets(&target), None);
+    }
+
+    #[test]
+    fn field_offsets_of_truncated_struct() {
+        let tp = IRType::new(0, vec![IRTypeEntry::Struct { alignment: None, num_of_fields: usize::MAX }]);
+        assert_eq!(tp.field_offsets(&IRTargetLayout::default()), None);
+    }
+}
@@ -8,6 +8,11 @@ pub mod assembly;
 pub mod instr;
 pub mod block;
 pub mod module;
+pub mod disassembly;
+pub mod builder;
+pub mod resolved;
+pub mod layout;
+pub mod visitor;
 
 pub use crate::okroshka::ir::core::*;
 pub use crate::okroshka::ir::datatype::*;
@@ -18,4 +23,8 @@ pub use crate::okroshka::ir::data::*;
 pub use crate::okroshka::ir::assembly::*;
 pub use crate::okroshka::ir::block::*;
 pub use crate::okroshka::ir::instr::*;
-pub use crate::okroshka::ir::module::*;
\ No newline at end of file
+pub use crate::okroshka::ir::module::*;
+pub use crate::okroshka::ir::builder::*;
+pub use crate::okroshka::ir::resolved::*;
+pub use crate::okroshka::ir::layout::*;
+pub use crate::okroshka::ir::visitor::*;
\ No newline at end of file
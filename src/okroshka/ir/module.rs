@@ -1,41 +1,97 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use crate::okroshka::ir::{
     IRIdentifier,
     IRError,
     IRType,
     IRTypeRef,
+    IRTypeEntry,
+    IRTargetLayout,
     IRFunctionDeclaration,
     IRFunction,
     IRSymbol,
+    IRSymbolStorage,
     IRStringLiteral,
     IRData,
+    IRDataStorage,
+    IRDataElement,
     IRInlineAssembly,
     IRInlineAssemblyParameterClass,
     IRBlock,
-    IRInstructionArgument
+    IRInstruction,
+    IRInstructionArgument,
+    IRModuleBuilder
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IRModuleSummary {
+    pub globals: usize,
+    pub externals: usize,
+    pub types: usize,
+    pub function_declarations: usize,
+    pub functions: usize,
+    pub data: usize,
+    pub string_literals: usize,
+    pub inline_assembly: usize,
+    pub instructions: usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    pub dedup_types: bool,
+    pub prune_types: bool,
+    pub prune_string_literals: bool,
+    pub coalesce_undefined: bool
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        NormalizeOptions {
+            dedup_types: true,
+            prune_types: true,
+            prune_string_literals: true,
+            coalesce_undefined: true
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NormalizeReport {
+    pub deduplicated_types: usize,
+    pub pruned_types: usize,
+    pub pruned_string_literals: usize,
+    pub coalesced_undefined: usize
+}
+
+impl NormalizeReport {
+    pub fn is_unchanged(&self) -> bool {
+        *self == NormalizeReport::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRModule {
-    globals: HashMap<String, IRSymbol>,
-    externals: HashMap<String, IRSymbol>,
+    globals: HashMap<Rc<str>, IRSymbol>,
+    externals: HashMap<Rc<str>, IRSymbol>,
     types: HashMap<IRIdentifier, IRType>,
     string_literals: HashMap<IRIdentifier, IRStringLiteral>,
     function_declarations: HashMap<IRIdentifier, IRFunctionDeclaration>,
-    functions: HashMap<String, IRFunction>,
-    data: HashMap<String, IRData>,
-    inline_asm: HashMap<IRIdentifier, IRInlineAssembly>
+    functions: HashMap<Rc<str>, IRFunction>,
+    data: HashMap<Rc<str>, IRData>,
+    inline_asm: HashMap<IRIdentifier, IRInlineAssembly>,
+    opcode_revision: Option<u64>
 }
 
 impl IRModule {
-    pub fn new(globals: HashMap<String, IRSymbol>,
-               externals: HashMap<String, IRSymbol>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(globals: HashMap<Rc<str>, IRSymbol>,
+               externals: HashMap<Rc<str>, IRSymbol>,
                types: HashMap<IRIdentifier, IRType>,
                string_literals: HashMap<IRIdentifier, IRStringLiteral>,
                function_declarations: HashMap<IRIdentifier, IRFunctionDeclaration>,
-               functions: HashMap<String, IRFunction>,
-               data: HashMap<String, IRData>,
+               functions: HashMap<Rc<str>, IRFunction>,
+               data: HashMap<Rc<str>, IRData>,
                inline_asm: HashMap<IRIdentifier, IRInlineAssembly>) -> Result<IRModule, IRError> {
         let module = IRModule {
             globals,
@@ -45,15 +101,42 @@ impl IRModule {
             function_declarations,
             functions,
             data,
-            inline_asm
+            inline_asm,
+            opcode_revision: None
         };
         module.check()?;
         Ok(module)
     }
 
+    pub fn new_unchecked(parts: IRModuleBuilder) -> IRModule {
+        IRModule {
+            globals: parts.globals,
+            externals: parts.externals,
+            types: parts.types,
+            string_literals: parts.string_literals,
+            function_declarations: parts.function_declarations,
+            functions: parts.functions,
+            data: parts.data,
+            inline_asm: parts.inline_asm,
+            opcode_revision: None
+        }
+    }
+
+    pub fn opcode_revision(&self) -> Option<u64> {
+        self.opcode_revision
+    }
+
+    pub(crate) fn set_opcode_revision(&mut self, revision: Option<u64>) {
+        self.opcode_revision = revision;
+    }
+
     pub fn globals(&self) -> impl Iterator<Item = &IRSymbol> {
-        self.globals.iter()
-            .map(| (_, value) | value)
+        self.globals.values()
+    }
+
+    pub fn globals_by_storage(&self, storage: IRSymbolStorage) -> impl Iterator<Item = &IRSymbol> {
+        self.globals()
+            .filter(move | sym | sym.storage() == storage)
     }
 
     pub fn is_global(&self, sym: &str) -> bool {
@@ -61,8 +144,7 @@ impl IRModule {
     }
 
     pub fn externals(&self) -> impl Iterator<Item = &IRSymbol> {
-        self.externals.iter()
-            .map(| (_, value) | value)
+        self.externals.values()
     }
 
     pub fn is_external(&self, sym: &str) -> bool {
@@ -70,17 +152,32 @@ impl IRModule {
     }
 
     pub fn types(&self) -> impl Iterator<Item = &IRType> {
-        self.types.iter()
-            .map(| (_, tp) | tp)
+        self.types.values()
+    }
+
+    pub fn types_sorted(&self) -> impl Iterator<Item = &IRType> {
+        let mut types = self.types().collect::<Vec<_>>();
+        types.sort_by_key(| tp | tp.identifier());
+        types.into_iter()
     }
 
     pub fn get_type(&self, id: IRIdentifier) -> Option<&IRType> {
         self.types.get(&id)
     }
 
+    pub fn resolve_type_ref(&self, typeref: IRTypeRef) -> Option<&IRTypeEntry> {
+        self.get_type(typeref.type_id)?
+            .at(typeref.type_index as u64)
+    }
+
     pub fn string_literals(&self) -> impl Iterator<Item = &IRStringLiteral>{
-        self.string_literals.iter()
-            .map(| (_, lit) | lit)
+        self.string_literals.values()
+    }
+
+    pub fn string_literals_sorted(&self) -> impl Iterator<Item = &IRStringLiteral> {
+        let mut literals = self.string_literals().collect::<Vec<_>>();
+        literals.sort_by_key(| literal | literal.identifier());
+        literals.into_iter()
     }
 
     pub fn get_string_literal(&self, id: IRIdentifier) -> Option<&IRStringLiteral>{
@@ -92,17 +189,111 @@ impl IRModule {
     }
 
     pub fn function_declarations(&self) -> impl Iterator<Item = &IRFunctionDeclaration> {
-        self.function_declarations.iter()
-            .map(| (_, decl) | decl)
+        self.function_declarations.values()
     }
 
     pub fn get_function(&self, name: &str) -> Option<&IRFunction> {
         self.functions.get(name)
     }
 
+    pub fn get_function_with_declaration(&self, name: &str) -> Option<(&IRFunction, &IRFunctionDeclaration)> {
+        let func = self.get_function(name)?;
+        let decl = self.get_function_declaration(func.declaration_id())?;
+        Some((func, decl))
+    }
+
     pub fn functions(&self) -> impl Iterator<Item = &IRFunction> {
-        self.functions.iter()
-            .map(| (_, func) | func)
+        self.functions.values()
+    }
+
+    pub fn functions_sorted(&self) -> impl Iterator<Item = &IRFunction> {
+        let mut functions = self.functions().collect::<Vec<_>>();
+        functions.sort_by_key(| func | func.name());
+        functions.into_iter()
+    }
+
+    pub(crate) fn functions_mut(&mut self) -> impl Iterator<Item = &mut IRFunction> {
+        self.functions.values_mut()
+    }
+
+    pub fn function_param_type(&self, func_name: &str) -> Option<&IRType> {
+        let func = self.get_function(func_name)?;
+        let decl = self.get_function_declaration(func.declaration_id())?;
+        self.get_type(decl.params_type())
+    }
+
+    pub fn function_return_type(&self, func_name: &str) -> Option<&IRType> {
+        let func = self.get_function(func_name)?;
+        let decl = self.get_function_declaration(func.declaration_id())?;
+        self.get_type(decl.return_type())
+    }
+
+    pub fn instructions(&self) -> impl Iterator<Item = (&str, usize, &IRInstruction)> {
+        self.functions()
+            .flat_map(| func | func.body()
+                .code()
+                .enumerate()
+                .map(move | (index, instr) | (func.name(), index, instr)))
+    }
+
+    pub fn is_leaf_function(&self, name: &str) -> Option<bool> {
+        let func = self.get_function(name)?;
+        Some(!func.body().code().any(| instr | instr.is_call()))
+    }
+
+    pub fn callers_of(&self, decl_id: IRIdentifier) -> Vec<&str> {
+        let mut callers = self.functions()
+            .filter(| func | func.body()
+                .code()
+                .any(| instr | matches!(instr.argument(), IRInstructionArgument::FunctionRef(id, _) if id == decl_id)))
+            .map(| func | func.name())
+            .collect::<Vec<_>>();
+        callers.sort_unstable();
+        callers
+    }
+
+    pub fn all_branch_edges(&self) -> Vec<(&str, usize, usize)> {
+        self.functions_sorted()
+            .flat_map(| func | func.body()
+                .code()
+                .enumerate()
+                .filter_map(move | (index, instr) | match instr.argument() {
+                    IRInstructionArgument::CodeRef(target) => Some((func.name(), index, target)),
+                    _ => None
+                }))
+            .collect()
+    }
+
+    pub fn opcode_histogram(&self) -> HashMap<&'static str, usize> {
+        let mut histogram = HashMap::new();
+        for (_, _, instr) in self.instructions() {
+            *histogram.entry(instr.mnemonic()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    pub fn function_sizes(&self) -> Vec<(&str, usize)> {
+        let mut sizes = self.functions()
+            .map(| func | (func.name(), func.body().len()))
+            .collect::<Vec<_>>();
+        sizes.sort_unstable_by(| (name1, size1), (name2, size2) | size2.cmp(size1).then(name1.cmp(name2)));
+        sizes
+    }
+
+    pub fn summary(&self) -> IRModuleSummary {
+        IRModuleSummary {
+            globals: self.globals.len(),
+            externals: self.externals.len(),
+            types: self.types.len(),
+            function_declarations: self.function_declarations.len(),
+            functions: self.functions.len(),
+            data: self.data.len(),
+            string_literals: self.string_literals.len(),
+            inline_assembly: self.inline_asm.len(),
+            instructions: self.functions()
+                .map(| func | func.body().len())
+                .sum()
+        }
     }
 
     pub fn get_data(&self, id: &str) -> Option<&IRData> {
@@ -110,8 +301,18 @@ impl IRModule {
     }
 
     pub fn data(&self) -> impl Iterator<Item = &IRData> {
-        self.data.iter()
-            .map(| (_, data) | data)
+        self.data.values()
+    }
+
+    pub fn data_by_storage(&self, storage: IRDataStorage) -> impl Iterator<Item = &IRData> {
+        self.data()
+            .filter(move | data | data.data_storage() == storage)
+    }
+
+    pub fn data_sorted(&self) -> impl Iterator<Item = &IRData> {
+        let mut data = self.data().collect::<Vec<_>>();
+        data.sort_by_key(| data | data.name());
+        data.into_iter()
     }
 
     pub fn get_inline_assembly(&self, id: IRIdentifier) -> Option<&IRInlineAssembly> {
@@ -119,127 +320,733 @@ impl IRModule {
     }
 
     pub fn inline_assembly(&self) -> impl Iterator<Item = &IRInlineAssembly> {
-        self.inline_asm.iter()
-            .map(| (_, inline_asm) | inline_asm)
+        self.inline_asm.values()
+    }
+
+    pub fn used_type_ids(&self) -> impl Iterator<Item = IRIdentifier> + '_ {
+        self.types.keys().copied()
+    }
+
+    pub fn used_function_decl_ids(&self) -> impl Iterator<Item = IRIdentifier> + '_ {
+        self.function_declarations.keys().copied()
+    }
+
+    pub fn used_string_literal_ids(&self) -> impl Iterator<Item = IRIdentifier> + '_ {
+        self.string_literals.keys().copied()
+    }
+
+    pub fn used_inline_asm_ids(&self) -> impl Iterator<Item = IRIdentifier> + '_ {
+        self.inline_asm.keys().copied()
+    }
+
+    pub fn next_free_type_id(&self) -> IRIdentifier {
+        self.used_type_ids()
+            .max()
+            .map(| id | id + 1)
+            .unwrap_or(0)
+    }
+
+    pub fn rename_function(&mut self, old: &str, new: &str) -> Result<(), IRError> {
+        if !self.functions.contains_key(old) {
+            return Err(IRError::FunctionNotFound(old.to_owned()));
+        } else if old == new {
+            return Ok(());
+        } else if self.functions.contains_key(new) {
+            return Err(IRError::DuplicateFunction(new.to_owned()));
+        } else if self.globals.contains_key(new) || self.externals.contains_key(new) {
+            return Err(IRError::DuplicateSymbol(new.to_owned()));
+        } else if self.data.contains_key(new) {
+            return Err(IRError::DuplicateData(new.to_owned()));
+        }
+        self.rename_symbol_references(old, new.into());
+        Ok(())
+    }
+
+    fn rename_symbol_references(&mut self, old: &str, new: Rc<str>) {
+        if let Some(mut func) = self.functions.remove(old) {
+            func.set_name(new.clone());
+            self.functions.insert(new.clone(), func);
+        }
+
+        for symbols in [&mut self.globals, &mut self.externals] {
+            if let Some(symbol) = symbols.remove(old) {
+                symbols.insert(new.clone(), symbol.renamed(new.clone()));
+            }
+        }
+
+        for decl in self.function_declarations.values_mut() {
+            if decl.name() == Some(old) {
+                decl.set_name(Some(new.to_string()));
+            }
+        }
+
+        for func in self.functions.values_mut() {
+            for instr in func.body_mut().code_mut() {
+                let name = match instr.function_ref_mut() {
+                    Some((_, name)) => name.as_mut(),
+                    None => instr.identifier_mut()
+                };
+                match name {
+                    Some(name) if name == old => *name = new.to_string(),
+                    _ => ()
+                }
+            }
+        }
+
+        for data in self.data.values_mut() {
+            for element in data.elements_mut() {
+                match element {
+                    IRDataElement::Pointer { base, .. } if base == old => *base = new.to_string(),
+                    _ => ()
+                }
+            }
+        }
+
+        for inline_asm in self.inline_asm.values_mut() {
+            for param in inline_asm.parameters_mut() {
+                match param.klass_mut() {
+                    IRInlineAssemblyParameterClass::ImmediateIdentifierBased(_, base, _) if base == old
+                        => *base = new.to_string(),
+                    _ => ()
+                }
+            }
+            for jump_target in inline_asm.jump_targets_mut() {
+                if jump_target.target_function() == old {
+                    jump_target.set_target_function(new.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn unused_string_literals(&self) -> Vec<IRIdentifier> {
+        let referenced = self.referenced_string_literals();
+        let mut unused = self.string_literals.keys()
+            .filter(| id | !referenced.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        unused.sort_unstable();
+        unused
+    }
+
+    pub fn prune_string_literals(&mut self) -> usize {
+        let referenced = self.referenced_string_literals();
+        let initial_len = self.string_literals.len();
+        self.string_literals.retain(| id, literal | literal.is_public() || referenced.contains(id));
+        initial_len - self.string_literals.len()
+    }
+
+    fn referenced_string_literals(&self) -> HashSet<IRIdentifier> {
+        let mut referenced = HashSet::new();
+        for (_, _, instr) in self.instructions() {
+            if let IRInstructionArgument::String(literal_id) = instr.argument() {
+                referenced.insert(literal_id);
+            }
+        }
+
+        for data in self.data() {
+            for element in data.elements() {
+                if let IRDataElement::StringPointer { base, .. } = element {
+                    referenced.insert(*base);
+                }
+            }
+        }
+
+        for inline_asm in self.inline_assembly() {
+            for param in inline_asm.parameters() {
+                if let IRInlineAssemblyParameterClass::ImmediateLiteralBased(_, literal_id, _) = param.klass() {
+                    referenced.insert(*literal_id);
+                }
+            }
+        }
+        referenced
+    }
+
+    pub fn dedup_types(&mut self) -> usize {
+        let mut ids = self.types.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+        let mut canonical: HashMap<&[IRTypeEntry], IRIdentifier> = HashMap::new();
+        let mut type_ids = HashMap::new();
+        for id in ids {
+            let content = self.types[&id].content();
+            match canonical.get(content) {
+                Some(&canonical_id) => { type_ids.insert(id, canonical_id); },
+                None => { canonical.insert(content, id); }
+            }
+        }
+
+        self.types.retain(| id, _ | !type_ids.contains_key(id));
+        self.remap_type_ids(&type_ids);
+        type_ids.len()
+    }
+
+    pub fn prune_types(&mut self) -> usize {
+        let referenced = self.referenced_types();
+        let initial_len = self.types.len();
+        self.types.retain(| id, _ | referenced.contains(id));
+        initial_len - self.types.len()
+    }
+
+    pub fn coalesce_undefined(&mut self) -> usize {
+        self.data.values_mut()
+            .map(| data | data.coalesce_undefined())
+            .sum()
+    }
+
+    pub fn normalize(&mut self, opts: NormalizeOptions) -> NormalizeReport {
+        let mut report = NormalizeReport::default();
+        if opts.dedup_types {
+            report.deduplicated_types = self.dedup_types();
+        }
+        if opts.prune_types {
+            report.pruned_types = self.prune_types();
+        }
+        if opts.prune_string_literals {
+            report.pruned_string_literals = self.prune_string_literals();
+        }
+        if opts.coalesce_undefined {
+            report.coalesced_undefined = self.coalesce_undefined();
+        }
+        report
+    }
+
+    fn referenced_types(&self) -> HashSet<IRIdentifier> {
+        let mut referenced = HashSet::new();
+        for decl in self.function_declarations() {
+            referenced.insert(decl.params_type());
+            referenced.insert(decl.return_type());
+        }
+
+        for func in self.functions() {
+            referenced.insert(func.locals_type());
+            referenced.extend(func.type_refs().map(| typeref | typeref.type_id));
+        }
+
+        for data in self.data() {
+            referenced.insert(data.data_type());
+        }
+
+        for inline_asm in self.inline_assembly() {
+            for param in inline_asm.parameters() {
+                referenced.extend(param.klass().type_refs().into_iter().map(| typeref | typeref.type_id));
+            }
+        }
+        referenced
+    }
+
+    fn remap_type_ids(&mut self, type_ids: &HashMap<IRIdentifier, IRIdentifier>) {
+        let remap = | id: IRIdentifier | type_ids.get(&id).copied().unwrap_or(id);
+
+        for decl in self.function_declarations.values_mut() {
+            decl.set_params_type(remap(decl.params_type()));
+            decl.set_return_type(remap(decl.return_type()));
+        }
+
+        for func in self.functions.values_mut() {
+            func.set_locals_type(remap(func.locals_type()));
+            for instr in func.body_mut().code_mut() {
+                if let Some(typeref) = instr.type_ref_mut() {
+                    typeref.type_id = remap(typeref.type_id);
+                }
+            }
+        }
+
+        for data in self.data.values_mut() {
+            data.set_data_type(remap(data.data_type()));
+        }
+
+        for inline_asm in self.inline_asm.values_mut() {
+            for param in inline_asm.parameters_mut() {
+                for typeref in param.klass_mut().type_refs_mut() {
+                    typeref.type_id = remap(typeref.type_id);
+                }
+            }
+        }
+    }
+
+    pub fn merge(mut self, other: IRModule) -> Result<IRModule, IRError> {
+        self.absorb(other)?;
+        self.check()?;
+        Ok(self)
+    }
+
+    pub fn merge_in_place(&mut self, other: IRModule) -> Result<(), IRError> {
+        let mut merged = self.clone();
+        merged.absorb(other)?;
+        merged.check()?;
+        *self = merged;
+        Ok(())
+    }
+
+    fn absorb(&mut self, other: IRModule) -> Result<(), IRError> {
+        let type_ids = IRModule::remap_identifiers(&self.types, &other.types)?;
+        let literal_ids = IRModule::remap_identifiers(&self.string_literals, &other.string_literals)?;
+        let decl_ids = IRModule::remap_identifiers(&self.function_declarations, &other.function_declarations)?;
+        let inline_asm_ids = IRModule::remap_identifiers(&self.inline_asm, &other.inline_asm)?;
+        let remap = | ids: &HashMap<IRIdentifier, IRIdentifier>, id: IRIdentifier | ids.get(&id).copied().unwrap_or(id);
+        let remap_typeref = | typeref: &mut IRTypeRef | typeref.type_id = remap(&type_ids, typeref.type_id);
+
+        for (name, symbol) in other.globals {
+            if self.globals.contains_key(&name) {
+                return Err(IRError::DuplicateSymbol(name.to_string()));
+            }
+            self.externals.remove(&name);
+            self.globals.insert(name, symbol);
+        }
+
+        for (name, symbol) in other.externals {
+            if !self.globals.contains_key(&name) {
+                self.externals.entry(name).or_insert(symbol);
+            }
+        }
+
+        for (id, mut tp) in other.types {
+            let id = remap(&type_ids, id);
+            tp.set_identifier(id);
+            self.types.insert(id, tp);
+        }
+
+        for (id, mut literal) in other.string_literals {
+            let id = remap(&literal_ids, id);
+            literal.set_identifier(id);
+            self.string_literals.insert(id, literal);
+        }
+
+        for (id, mut decl) in other.function_declarations {
+            let id = remap(&decl_ids, id);
+            decl.set_identifier(id);
+            decl.set_params_type(remap(&type_ids, decl.params_type()));
+            decl.set_return_type(remap(&type_ids, decl.return_type()));
+            self.function_declarations.insert(id, decl);
+        }
+
+        for (name, mut func) in other.functions {
+            if self.functions.contains_key(&name) {
+                return Err(IRError::DuplicateFunction(name.to_string()));
+            }
+            func.set_declaration_id(remap(&decl_ids, func.declaration_id()));
+            func.set_locals_type(remap(&type_ids, func.locals_type()));
+            for instr in func.body_mut().code_mut() {
+                if let Some(typeref) = instr.type_ref_mut() {
+                    remap_typeref(typeref);
+                } else if let Some(literal_id) = instr.string_ref_mut() {
+                    *literal_id = remap(&literal_ids, *literal_id);
+                } else if let Some((decl_id, _)) = instr.function_ref_mut() {
+                    *decl_id = remap(&decl_ids, *decl_id);
+                } else if let Some(inline_asm_id) = instr.inline_asm_ref_mut() {
+                    *inline_asm_id = remap(&inline_asm_ids, *inline_asm_id);
+                }
+            }
+            self.functions.insert(name, func);
+        }
+
+        for (name, mut data) in other.data {
+            if self.data.contains_key(&name) {
+                return Err(IRError::DuplicateData(name.to_string()));
+            }
+            data.set_data_type(remap(&type_ids, data.data_type()));
+            for element in data.elements_mut() {
+                if let IRDataElement::StringPointer { base, .. } = element {
+                    *base = remap(&literal_ids, *base);
+                }
+            }
+            self.data.insert(name, data);
+        }
+
+        for (id, mut inline_asm) in other.inline_asm {
+            let id = remap(&inline_asm_ids, id);
+            inline_asm.set_identifier(id);
+            for param in inline_asm.parameters_mut() {
+                match param.klass_mut() {
+                    IRInlineAssemblyParameterClass::ImmediateLiteralBased(typeref, literal_id, _) => {
+                        remap_typeref(typeref);
+                        *literal_id = remap(&literal_ids, *literal_id);
+                    },
+                    IRInlineAssemblyParameterClass::ImmediateConstant(typeref, _) |
+                    IRInlineAssemblyParameterClass::ImmediateIdentifierBased(typeref, _, _) |
+                    IRInlineAssemblyParameterClass::Read(typeref, _) |
+                    IRInlineAssemblyParameterClass::Load(typeref, _) |
+                    IRInlineAssemblyParameterClass::Store(typeref, _) |
+                    IRInlineAssemblyParameterClass::LoadStore(typeref, _)
+                        => remap_typeref(typeref),
+                    IRInlineAssemblyParameterClass::ReadStore(typeref1, _, typeref2, _) => {
+                        remap_typeref(typeref1);
+                        remap_typeref(typeref2);
+                    }
+                }
+            }
+            self.inline_asm.insert(id, inline_asm);
+        }
+        Ok(())
+    }
+
+    fn remap_identifiers<T>(existing: &HashMap<IRIdentifier, T>, incoming: &HashMap<IRIdentifier, T>) -> Result<HashMap<IRIdentifier, IRIdentifier>, IRError> {
+        let mut next_id = existing.keys()
+            .chain(incoming.keys())
+            .max()
+            .map(| &id | id.checked_add(1))
+            .unwrap_or(Some(0));
+        let mut incoming_ids = incoming.keys().copied().collect::<Vec<_>>();
+        incoming_ids.sort_unstable();
+        let mut remapped = HashMap::new();
+        for id in incoming_ids {
+            if existing.contains_key(&id) {
+                let new_id = next_id.ok_or(IRError::IdentifierOverflow)?;
+                next_id = new_id.checked_add(1);
+                remapped.insert(id, new_id);
+            } else {
+                remapped.insert(id, id);
+            }
+        }
+        Ok(remapped)
     }
 
     fn check(&self) -> Result<(), IRError> {
+        match self.validate().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(())
+        }
+    }
+
+    pub fn validate(&self) -> Vec<IRError> {
+        let mut errors = Vec::new();
+        for name in self.globals.keys() {
+            if self.externals.contains_key(name) {
+                errors.push(IRError::GlobalExternalConflict(name.to_string()));
+            }
+        }
+
+        for tp in self.types.values() {
+            if !tp.is_well_formed() {
+                errors.push(IRError::MalformedType(tp.identifier()));
+            }
+        }
+
         for (&func_decl_id, func_decl) in self.function_declarations.iter() {
             if func_decl_id != func_decl.identifier() {
-                Err(IRError("Expected IR function declaration identifier to match respective index key".to_owned()))?;
+                errors.push(IRError::FunctionDeclIdMismatch(func_decl_id));
             }
 
-            self.check_type_id(func_decl.params_type())?;
-            self.check_type_id(func_decl.return_type())?;
+            errors.extend(self.check_type_id(func_decl.params_type()).err()
+                .map(| err | err.in_function_declaration(func_decl_id)));
+            errors.extend(self.check_type_id(func_decl.return_type()).err()
+                .map(| err | err.in_function_declaration(func_decl_id)));
         }
 
         for (func_id, func) in self.functions.iter() {
-            if func_id != func.name() {
-                Err(IRError("Expected IR function declaration identifier to match respective index key".to_owned()))?;
+            if &**func_id != func.name() {
+                errors.push(IRError::FunctionNameMismatch(func_id.to_string()));
             }
 
-            self.check_function_declaration_id(func.declaration_id())?;
-            self.check_type_id(func.locals_type())?;
-            self.check_block(func.body())?;
+            errors.extend(self.check_function_declaration_id(func.declaration_id()).err()
+                .map(| err | err.in_function(func_id, None)));
+            errors.extend(self.check_type_id(func.locals_type()).err()
+                .map(| err | err.in_function(func_id, None)));
+            self.check_block(func_id, func.body(), &mut errors);
         }
 
         for (data_name, data) in self.data.iter() {
-            if data_name != data.name() {
-                Err(IRError("Expected IR data name to match respective index key".to_owned()))?;
+            if &**data_name != data.name() {
+                errors.push(IRError::DataNameMismatch(data_name.to_string()));
             }
 
-            self.check_type_id(data.data_type())?;
+            errors.extend(self.check_type_id(data.data_type()).err()
+                .map(| err | err.in_data(data_name, None)));
+            self.check_data(data_name, data, &mut errors);
         }
 
         for (&inline_asm_id, inline_asm) in self.inline_asm.iter() {
             if inline_asm_id != inline_asm.identifier() {
-                Err(IRError("Expected IR inline assembly identifier to match respective index key".to_owned()))?;
+                errors.push(IRError::InlineAssemblyIdMismatch(inline_asm_id));
             }
 
             for param in inline_asm.parameters() {
                 match param.klass() {
                     IRInlineAssemblyParameterClass::ImmediateConstant(typeref, _)
-                        => self.check_type_ref(*typeref)?,
+                        => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
 
                     IRInlineAssemblyParameterClass::ImmediateIdentifierBased(typeref, _, _)
-                    => self.check_type_ref(*typeref)?,
+                    => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
 
                     IRInlineAssemblyParameterClass::ImmediateLiteralBased(typeref, literal_id, _)
                         => {
-                        self.check_type_ref(*typeref)?;
-                        self.check_string_literal(*literal_id)?;
+                        errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id)));
+                        errors.extend(self.check_string_literal(*literal_id).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id)));
                     }
 
                     IRInlineAssemblyParameterClass::Read(typeref, _)
-                        => self.check_type_ref(*typeref)?,
+                        => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
 
                     IRInlineAssemblyParameterClass::Load(typeref, _)
-                        => self.check_type_ref(*typeref)?,
+                        => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
                     
                     IRInlineAssemblyParameterClass::Store(typeref, _)
-                        => self.check_type_ref(*typeref)?,
+                        => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
                         
                     IRInlineAssemblyParameterClass::LoadStore(typeref, _)
-                        => self.check_type_ref(*typeref)?,
+                        => errors.extend(self.check_type_ref(*typeref).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id))),
                     
                     IRInlineAssemblyParameterClass::ReadStore(typeref1, _, typeref2, _)
                         => {
-                        self.check_type_ref(*typeref1)?;
-                        self.check_type_ref(*typeref2)?;
+                        errors.extend(self.check_type_ref(*typeref1).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id)));
+                        errors.extend(self.check_type_ref(*typeref2).err()
+                            .map(| err | err.in_inline_assembly(inline_asm_id)));
                     }
                 };
             }
 
+            for alias in inline_asm.template_placeholders() {
+                if inline_asm.get_by_alias(alias).is_none() {
+                    errors.push(IRError::UnknownTemplateAlias(alias.to_owned()).in_inline_assembly(inline_asm_id));
+                }
+            }
+
             for jump_target in inline_asm.jump_targets() {
-                let func = self.get_function(jump_target.target_function())
-                    .ok_or(IRError("Unable to find specified jump target function".to_owned()))?;
-                if jump_target.target_function_offset() > func.body().len() {
-                    Err(IRError("Expected IR inline assembly jump target offset exceeds respective function body length".to_owned()))?;
+                match self.get_function(jump_target.target_function()) {
+                    Some(func) => if jump_target.target_function_offset() > func.body().len() {
+                        errors.push(IRError::JumpTargetOutOfRange {
+                            function: jump_target.target_function().to_owned(),
+                            offset: jump_target.target_function_offset(),
+                            len: func.body().len()
+                        }.in_inline_assembly(inline_asm_id));
+                    },
+                    None => errors.push(IRError::JumpTargetFunctionNotFound(jump_target.target_function().to_owned())
+                        .in_inline_assembly(inline_asm_id))
                 }
             }
         }
 
-        Ok(())
+        self.check_global_inline_assembly_aliases(&mut errors);
+
+        errors
     }
 
-    fn check_block(&self, block: &IRBlock) -> Result<(), IRError> {
-        for instr in block.code() {
-            match instr.argument() {
+    pub fn check_strict(&self) -> Result<(), IRError> {
+        match self.validate_strict().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(())
+        }
+    }
+
+    pub fn validate_strict(&self) -> Vec<IRError> {
+        let mut errors = self.validate();
+
+        let mut leaders = HashMap::new();
+        for inline_asm in self.inline_asm.values() {
+            for jump_target in inline_asm.jump_targets() {
+                let func = match self.get_function(jump_target.target_function()) {
+                    Some(func) => func,
+                    None => continue
+                };
+                let offset = jump_target.target_function_offset();
+                if offset == func.body().len() {
+                    errors.push(IRError::JumpTargetOutOfRange {
+                        function: func.name().to_owned(),
+                        offset,
+                        len: func.body().len()
+                    }.in_inline_assembly(inline_asm.identifier()));
+                } else if offset < func.body().len() && !leaders.entry(func.name())
+                    .or_insert_with(|| func.body().basic_blocks().into_iter()
+                        .map(| block | block.start)
+                        .collect::<HashSet<_>>())
+                    .contains(&offset) {
+                    errors.push(IRError::JumpTargetNotLeader {
+                        function: func.name().to_owned(),
+                        offset
+                    }.in_inline_assembly(inline_asm.identifier()));
+                }
+            }
+        }
+
+        for func_decl in self.function_declarations.values() {
+            errors.extend(self.check_struct_type(func_decl.identifier(), func_decl.params_type()).err());
+            errors.extend(self.check_struct_type(func_decl.identifier(), func_decl.return_type()).err());
+        }
+
+        for func in self.functions.values() {
+            match self.get_function_declaration(func.declaration_id()).and_then(| decl | decl.name()) {
+                Some(decl_name) if decl_name != func.name() => errors.push(IRError::FunctionDeclNameMismatch {
+                    function: func.name().to_owned(),
+                    decl_id: func.declaration_id(),
+                    decl_name: decl_name.to_owned()
+                }),
+                _ => ()
+            }
+        }
+
+        let target = IRTargetLayout::default();
+        for data in self.data.values() {
+            if data.elements().any(| element | matches!(element, IRDataElement::Unknown { .. })) {
+                continue
+            }
+            let type_size = match self.get_type(data.data_type()).map(| tp | tp.size_of(0, &target)) {
+                Some(Ok(type_size)) => type_size,
+                Some(Err(err)) => {
+                    errors.push(err.in_data(data.name(), None));
+                    continue
+                },
+                None => continue
+            };
+            match data.total_size(self, &target) {
+                Some(size) if size <= type_size => (),
+                Some(size) => errors.push(IRError::DataSizeExceedsType {
+                    data: data.name().to_owned(),
+                    size,
+                    type_size
+                }),
+                None => errors.push(IRError::DataSizeUnavailable(data.name().to_owned()))
+            }
+        }
+
+        for func in self.functions.values() {
+            for (index, instr) in func.body().code().enumerate() {
+                let value = match instr.argument() {
+                    IRInstructionArgument::Float32(x) => x as f64,
+                    IRInstructionArgument::Float64(x) => x,
+                    _ => continue
+                };
+                if !value.is_finite() {
+                    errors.push(IRError::NonFiniteFloat(value).in_function(func.name(), Some(index)));
+                }
+            }
+        }
+
+        for data in self.data.values() {
+            for (index, element) in data.elements().enumerate() {
+                let value = match element {
+                    IRDataElement::Float32(x) => *x as f64,
+                    IRDataElement::Float64(x) => *x,
+                    IRDataElement::LongDouble(x) => x.to_f64(),
+                    _ => continue
+                };
+                if !value.is_finite() {
+                    errors.push(IRError::NonFiniteFloat(value).in_data(data.name(), Some(index)));
+                }
+            }
+        }
+
+        let mut global_inline_asm = self.inline_asm.values()
+            .filter(| inline_asm | inline_asm.is_global())
+            .collect::<Vec<_>>();
+        global_inline_asm.sort_by_key(| inline_asm | inline_asm.identifier());
+        for inline_asm in global_inline_asm {
+            if inline_asm.jump_targets().next().is_some() {
+                errors.push(IRError::GlobalInlineAssemblyJumpTargets(inline_asm.identifier()));
+            }
+        }
+
+        errors
+    }
+
+    fn check_global_inline_assembly_aliases(&self, errors: &mut Vec<IRError>) {
+        let mut global_aliases = HashMap::new();
+        for inline_asm in self.inline_assembly().filter(| inline_asm | inline_asm.is_global()) {
+            let aliases = inline_asm.parameters()
+                .flat_map(| param | param.aliases())
+                .chain(inline_asm.jump_targets().flat_map(| target | target.aliases()));
+            for alias in aliases {
+                match global_aliases.insert(alias, inline_asm.identifier()) {
+                    Some(other_id) if other_id != inline_asm.identifier()
+                        => errors.push(IRError::DuplicateGlobalAlias(alias.to_owned())),
+                    _ => ()
+                }
+            }
+        }
+    }
+
+    fn check_block(&self, function: &str, block: &IRBlock, errors: &mut Vec<IRError>) {
+        for (index, instr) in block.code().enumerate() {
+            let result = match instr.argument() {
                 IRInstructionArgument::CodeRef(coderef)
-                    => if coderef > block.len() {
-                    Err(IRError("IR instruction argument code reference exceeds respective block boundaries".to_owned()))?
+                    => match block.at(coderef) {
+                    _ if coderef > block.len() => Err(IRError::CodeRefOutOfRange { target: coderef, len: block.len() }),
+                    Some(target) if instr.is_branch() && target.is_forbidden_branch_target()
+                        => Err(IRError::CodeRefForbiddenTarget { target: coderef, mnemonic: target.mnemonic().to_owned() }),
+                    _ => Ok(())
                 },
                 IRInstructionArgument::String(str_id)
-                    => self.check_string_literal(str_id)?,
+                    => self.check_string_literal(str_id),
                 IRInstructionArgument::TypeRef(typeref)
-                    => self.check_type_ref(typeref)?,
+                    => self.check_type_ref(typeref),
                 IRInstructionArgument::FunctionRef(decl_id, _)
-                    => self.check_function_declaration_id(decl_id)?,
-                _ => ()
-            }
+                    => self.check_function_declaration_id(decl_id),
+                _ => Ok(())
+            };
+            errors.extend(result.err().map(| err | err.in_function(function, Some(index))));
         }
+    }
 
+    pub(crate) fn check_function_typing(&self, func: &IRFunction) -> Result<(), IRError> {
+        self.check_function_declaration_id(func.declaration_id())
+            .and_then(| _ | self.check_type_id(func.locals_type()))
+            .map_err(| err | err.in_function(func.name(), None))?;
+        for (index, instr) in func.body().code().enumerate() {
+            match instr.argument() {
+                IRInstructionArgument::TypeRef(typeref)
+                    => self.check_type_ref(typeref),
+                IRInstructionArgument::FunctionRef(decl_id, _)
+                    => self.check_function_declaration_id(decl_id),
+                _ => Ok(())
+            }.map_err(| err | err.in_function(func.name(), Some(index)))?;
+        }
         Ok(())
     }
 
+    fn check_data(&self, name: &str, data: &IRData, errors: &mut Vec<IRError>) {
+        for (index, element) in data.elements().enumerate() {
+            let result = match element {
+                IRDataElement::Pointer { base, .. }
+                    => self.check_symbol(base),
+                IRDataElement::StringPointer { base, .. }
+                    => self.check_string_literal(*base),
+                _ => Ok(())
+            };
+            errors.extend(result.err().map(| err | err.in_data(name, Some(index))));
+        }
+    }
+
+    fn check_symbol(&self, name: &str) -> Result<(), IRError> {
+        if self.globals.contains_key(name) || self.externals.contains_key(name) ||
+            self.data.contains_key(name) || self.functions.contains_key(name) {
+            Ok(())
+        } else {
+            Err(IRError::SymbolNotFound(name.to_owned()))
+        }
+    }
+
     fn check_function_declaration_id(&self, id: IRIdentifier) -> Result<(), IRError> {
         match self.function_declarations.get(&id) {
             Some(_) => Ok(()),
-            None => Err(IRError("Provided IR function declaration identifier does not exist in the module".to_owned()))
+            None => Err(IRError::FunctionDeclNotFound(id))
         }
     }
 
     fn check_string_literal(&self, id: IRIdentifier) -> Result<(), IRError> {
         match self.string_literals.get(&id) {
             Some(_) => Ok(()),
-            None => Err(IRError("Provided IR string literal identifier does not exist in the module".to_owned()))
+            None => Err(IRError::StringLiteralNotFound(id))
         }
     }
 
     fn check_type_id(&self, id: IRIdentifier) -> Result<(), IRError> {
         match self.types.get(&id) {
             Some(_) => Ok(()),
-            None => Err(IRError("Provided IR type identifier does not exist in the module".to_owned()))
+            None => Err(IRError::TypeIdNotFound(id))
+        }
+    }
+
+    fn check_struct_type(&self, decl_id: IRIdentifier, type_id: IRIdentifier) -> Result<(), IRError> {
+        match self.types.get(&type_id).and_then(| tp | tp.at(0)) {
+            Some(IRTypeEntry::Struct { .. }) => Ok(()),
+            _ => Err(IRError::FunctionDeclTypeNotStruct { decl_id, type_id })
         }
     }
 
@@ -248,9 +1055,732 @@ impl IRModule {
             Some(tp) => if typeref.type_index < tp.len() {
                 Ok(())
             } else {
-                Err(IRError("Provided IR type index exceeds respective IR type length".to_owned()))
+                Err(IRError::TypeIndexOutOfRange {
+                    type_id: typeref.type_id,
+                    index: typeref.type_index,
+                    len: tp.len()
+                })
             },
-            None => Err(IRError("Provided IR type identifier does not exist in the module".to_owned()))
+            None => Err(IRError::TypeIdNotFound(typeref.type_id))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::ir::{IRInlineAssemblyIndexedAlias, IRInlineAssemblyJumpTarget, IRInlineAssemblyParameter, IRInlineAssemblyParameterConstraint, IRDataStorage, IRSymbolAttributes};
+    use crate::okroshka::testing::{load_fixture, load_module};
+
+    const TWO_FUNCTIONS: &str = r#"{
+        "globals": [], "externals": [],
+        "types": [{"identifier": 0, "type": []}],
+        "function_declarations": [
+            {"identifier": 0, "name": "first", "parameters": 0, "vararg": false, "returns": 0},
+            {"identifier": 1, "name": "second", "parameters": 0, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "first", "locals": 0, "body": [
+                {"opcode": "push", "arg": 1}, {"opcode": "push", "arg": 2}, {"opcode": "iadd"}, {"opcode": "ret"}
+            ]},
+            {"identifier": 1, "name": "second", "locals": 0, "body": [
+                {"opcode": "invoke", "arg": {"identifier": 0, "name": "first"}}, {"opcode": "ret"}
+            ]}
+        ],
+        "data": [], "string_literals": [], "inline_assembly": []
+    }"#;
+
+    #[test]
+    fn instructions_visit_every_function_body() {
+        let module = load_module(TWO_FUNCTIONS);
+        let mut visited = module.instructions()
+            .map(| (name, index, instr) | (name, index, instr.mnemonic()))
+            .collect::<Vec<_>>();
+        visited.sort_unstable();
+        assert_eq!(visited, vec![
+            ("first", 0, "push"),
+            ("first", 1, "push"),
+            ("first", 2, "iadd"),
+            ("first", 3, "ret"),
+            ("second", 0, "invoke"),
+            ("second", 1, "ret")
+        ]);
+    }
+
+    #[test]
+    fn opcode_histogram_counts_mnemonics() {
+        let module = load_module(TWO_FUNCTIONS);
+        let histogram = module.opcode_histogram();
+        assert_eq!(histogram.len(), 4);
+        assert_eq!(histogram["push"], 2);
+        assert_eq!(histogram["iadd"], 1);
+        assert_eq!(histogram["invoke"], 1);
+        assert_eq!(histogram["ret"], 2);
+    }
+
+    fn inline_asm_jumping_to(offset: usize) -> IRInlineAssembly {
+        let target = IRInlineAssemblyJumpTarget::new(0, vec!["l".to_owned()], "f".to_owned(), offset);
+        IRInlineAssembly::new(0, false, "jmp %l[l]".to_owned(), HashMap::new(), HashSet::new(),
+            HashMap::from([(0, target)])).unwrap()
+    }
+
+    #[test]
+    fn check_validates_jump_target_offsets() {
+        let body = vec![
+            IRInstruction::pushi64(1),
+            IRInstruction::branch(3),
+            IRInstruction::pushi64(2),
+            IRInstruction::ret
+        ];
+        let strict_jump_target_errors = | offset | module_with(0, body.clone(), Vec::new(), vec![inline_asm_jumping_to(offset)])
+            .unwrap()
+            .validate_strict()
+            .into_iter()
+            .filter_map(| err | match err {
+                IRError::InInlineAssembly { error, .. } => Some(*error),
+                _ => None
+            })
+            .collect::<Vec<_>>();
+        for offset in [0, 2, 3] {
+            assert!(strict_jump_target_errors(offset).is_empty(), "{}", offset);
         }
+        assert!(matches!(strict_jump_target_errors(1).as_slice(), [IRError::JumpTargetNotLeader { offset: 1, .. }]));
+        assert!(matches!(strict_jump_target_errors(4).as_slice(), [IRError::JumpTargetOutOfRange { offset: 4, len: 4, .. }]));
+        match module_with(0, body, Vec::new(), vec![inline_asm_jumping_to(5)]) {
+            Err(IRError::InInlineAssembly { error, .. }) =>
+                assert!(matches!(*error, IRError::JumpTargetOutOfRange { offset: 5, len: 4, .. })),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn sorted_accessors_are_stable_across_loads() {
+        let orders = | module: &IRModule | (
+            module.types_sorted().map(| tp | tp.identifier()).collect::<Vec<_>>(),
+            module.functions_sorted().map(| func | func.name().to_owned()).collect::<Vec<_>>(),
+            module.data_sorted().map(| data | data.name().to_owned()).collect::<Vec<_>>(),
+            module.string_literals_sorted().map(| literal | literal.identifier()).collect::<Vec<_>>()
+        );
+        let fixture = orders(&load_fixture("module.json"));
+        assert_eq!(orders(&load_fixture("module.json")), fixture);
+        assert_eq!(fixture.0, vec![0, 1, 2]);
+        assert_eq!(fixture.2, vec!["counter", "msg"]);
+        let functions = orders(&load_module(TWO_FUNCTIONS)).1;
+        assert_eq!(orders(&load_module(TWO_FUNCTIONS)).1, functions);
+        assert_eq!(functions, vec!["first", "second"]);
+    }
+
+    fn module_with(declaration: IRIdentifier, body: Vec<IRInstruction>,
+                   data: Vec<IRData>, inline_asm: Vec<IRInlineAssembly>) -> Result<IRModule, IRError> {
+        let types = HashMap::from([
+            (0, IRType::new(0, Vec::new())),
+            (1, IRType::new(1, vec![IRTypeEntry::Int64 { alignment: None }]))
+        ]);
+        let function_declarations = HashMap::from([(0, IRFunctionDeclaration::new(0, Some("f".to_owned()), 0, false, 0))]);
+        let functions = HashMap::from([(Rc::from("f"), IRFunction::new("f", declaration, 0, IRBlock::new(body)))]);
+        let data = data.into_iter()
+            .map(| data | (Rc::from(data.name()), data))
+            .collect();
+        let inline_asm = inline_asm.into_iter()
+            .map(| inline_asm | (inline_asm.identifier(), inline_asm))
+            .collect();
+        IRModule::new(HashMap::new(), HashMap::new(), types, HashMap::new(),
+            function_declarations, functions, data, inline_asm)
+    }
+
+    fn module_with_function(declaration: IRIdentifier, body: Vec<IRInstruction>) -> Result<IRModule, IRError> {
+        module_with(declaration, body, Vec::new(), Vec::new())
+    }
+
+    #[test]
+    fn check_reports_missing_function_declaration() {
+        match module_with_function(7, vec![IRInstruction::ret]) {
+            Err(IRError::InFunction { function, index: None, error }) => {
+                assert_eq!(function, "f");
+                assert!(matches!(*error, IRError::FunctionDeclNotFound(7)));
+            },
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn check_reports_type_index_out_of_range() {
+        let err = module_with_function(0, vec![IRInstruction::getlocal(IRTypeRef::new(0, 3)), IRInstruction::ret])
+            .unwrap_err();
+        match &err {
+            IRError::InFunction { index: Some(0), error, .. } =>
+                assert!(matches!(**error, IRError::TypeIndexOutOfRange { type_id: 0, index: 3, len: 0 })),
+            other => panic!("unexpected error {:?}", other)
+        }
+        assert_eq!(err.to_string(), "in IR function f, instruction 0: Provided IR type index 3 exceeds IR type 0 length (0)");
+    }
+
+    fn global_inline_asm_with_alias(id: IRIdentifier, alias: &str) -> IRInlineAssembly {
+        let param = IRInlineAssemblyParameter::new(0, vec![alias.to_owned()],
+            IRInlineAssemblyParameterClass::ImmediateConstant(IRTypeRef::new(1, 0), 1),
+            IRInlineAssemblyParameterConstraint::None);
+        IRInlineAssembly::new(id, true, format!("nop %[{}]", alias), HashMap::from([(0, param)]),
+            HashSet::new(), HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn check_reports_global_inline_assembly_alias_collision() {
+        let inline_asm = vec![global_inline_asm_with_alias(0, "x"), global_inline_asm_with_alias(1, "x")];
+        match module_with(0, vec![IRInstruction::ret], Vec::new(), inline_asm) {
+            Err(IRError::DuplicateGlobalAlias(alias)) => assert_eq!(alias, "x"),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn check_reports_dangling_data_pointer() {
+        let data = IRData::new("ptr".to_owned(), IRDataStorage::Global, 1, vec![
+            IRDataElement::Pointer { base: "missing".to_owned(), offset: 0 }
+        ]);
+        match module_with(0, vec![IRInstruction::ret], vec![data], Vec::new()) {
+            Err(IRError::InData { data, index: Some(0), error }) => {
+                assert_eq!(data, "ptr");
+                assert!(matches!(*error, IRError::SymbolNotFound(ref name) if name == "missing"));
+            },
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn check_reports_unknown_string_pointer_literal() {
+        let data = IRData::new("str".to_owned(), IRDataStorage::Global, 1, vec![
+            IRDataElement::StringPointer { base: 3, offset: 0 }
+        ]);
+        match module_with(0, vec![IRInstruction::ret], vec![data], Vec::new()) {
+            Err(IRError::InData { index: Some(0), error, .. }) =>
+                assert!(matches!(*error, IRError::StringLiteralNotFound(3))),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    const THIRD_FUNCTION: &str = r#"{
+        "globals": [], "externals": [],
+        "types": [{"identifier": 0, "type": []}],
+        "function_declarations": [
+            {"identifier": 0, "name": "third", "parameters": 0, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "third", "locals": 0, "body": [
+                {"opcode": "invoke", "arg": {"identifier": 0, "name": "third"}}, {"opcode": "ret"}
+            ]}
+        ],
+        "data": [], "string_literals": [], "inline_assembly": []
+    }"#;
+
+    #[test]
+    fn merge_remaps_colliding_identifiers() {
+        let module = load_module(TWO_FUNCTIONS).merge(load_module(THIRD_FUNCTION)).unwrap();
+        let third = module.get_function("third").unwrap();
+        assert_eq!(third.declaration_id(), 2);
+        assert_eq!(third.locals_type(), 1);
+        assert_eq!(third.body().at(0), Some(&IRInstruction::invoke(2, Some("third".to_owned()))));
+        assert_eq!(module.get_function("second").unwrap().declaration_id(), 1);
+    }
+
+    #[test]
+    fn merge_in_place_failure_leaves_module_intact() {
+        let mut module = load_module(TWO_FUNCTIONS);
+        let original = module.clone();
+        assert!(matches!(module.merge_in_place(original.clone()), Err(IRError::DuplicateFunction(_))));
+        assert_eq!(module, original);
+
+        module.merge_in_place(load_module(THIRD_FUNCTION)).unwrap();
+        assert_eq!(module, original.merge(load_module(THIRD_FUNCTION)).unwrap());
+    }
+
+    #[test]
+    fn remap_identifiers_reports_overflow() {
+        let ids = HashMap::from([(IRIdentifier::MAX, ())]);
+        assert!(matches!(IRModule::remap_identifiers(&ids, &ids), Err(IRError::IdentifierOverflow)));
+    }
+
+    const STRING_LITERALS: &str = r#"{
+        "globals": [], "externals": [],
+        "types": [{"identifier": 0, "type": []}, {"identifier": 1, "type": [{"type": "word"}]}],
+        "function_declarations": [
+            {"identifier": 0, "name": "f", "parameters": 0, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "f", "locals": 0, "body": [{"opcode": "pushstring", "arg": 0}, {"opcode": "ret"}]}
+        ],
+        "data": [
+            {"identifier": "ptr", "storage": "global", "type": 1, "value": [{"class": "string_pointer", "string": 1, "offset": 0}]}
+        ],
+        "string_literals": [
+            {"id": 0, "public": false, "type": "multibyte", "literal": "used"},
+            {"id": 1, "public": false, "type": "multibyte", "literal": "pointed"},
+            {"id": 2, "public": false, "type": "multibyte", "literal": "unused"},
+            {"id": 3, "public": true, "type": "multibyte", "literal": "exported"}
+        ],
+        "inline_assembly": []
+    }"#;
+
+    #[test]
+    fn unused_string_literals_skip_code_and_data_references() {
+        let module = load_module(STRING_LITERALS);
+        assert_eq!(module.unused_string_literals(), vec![2, 3]);
+    }
+
+    #[test]
+    fn prune_string_literals_keeps_public_literals() {
+        let mut module = load_module(STRING_LITERALS);
+        assert_eq!(module.prune_string_literals(), 1);
+        assert!(module.get_string_literal(2).is_none());
+        assert!(module.get_string_literal(3).is_some());
+        assert!(module.check().is_ok());
+    }
+
+    const RENAMED_REFERENCES: &str = r#"{
+        "globals": [{"identifier": "f", "type": "global"}], "externals": [],
+        "types": [{"identifier": 0, "type": []}, {"identifier": 1, "type": [{"type": "word"}]}],
+        "function_declarations": [
+            {"identifier": 0, "name": "f", "parameters": 0, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "f", "locals": 0, "body": [
+                {"opcode": "getglobal", "arg": {"data": "f"}},
+                {"opcode": "invoke", "arg": {"identifier": 0, "name": "f"}},
+                {"opcode": "inlineasm", "arg": 0},
+                {"opcode": "ret"}
+            ]}
+        ],
+        "data": [
+            {"identifier": "ptr", "storage": "global", "type": 1, "value": [{"class": "pointer", "reference": "f", "offset": 0}]}
+        ],
+        "string_literals": [],
+        "inline_assembly": [
+            {"identifier": 0, "global": false, "template": "jmp %[l]", "parameters": [], "clobbers": [], "jump_targets": [
+                {"identifier": 0, "names": ["l"], "function": "f", "target": 0}
+            ]}
+        ]
+    }"#;
+
+    #[test]
+    fn rename_function_rewrites_references() {
+        let mut module = load_module(RENAMED_REFERENCES);
+        module.rename_function("f", "g").unwrap();
+        assert!(module.get_function("f").is_none());
+        let func = module.get_function("g").unwrap();
+        assert_eq!(func.body().at(0), Some(&IRInstruction::getglobal("g".to_owned())));
+        assert_eq!(func.body().at(1), Some(&IRInstruction::invoke(0, Some("g".to_owned()))));
+        assert!(module.is_global("g"));
+        assert!(!module.is_global("f"));
+        assert_eq!(module.get_function_declaration(0).and_then(| decl | decl.name()), Some("g"));
+        assert_eq!(module.get_data("ptr").unwrap().elements().next(),
+            Some(&IRDataElement::Pointer { base: "g".to_owned(), offset: 0 }));
+        let target = module.get_inline_assembly(0).unwrap().jump_targets().next().unwrap();
+        assert_eq!(target.target_function(), "g");
+    }
+
+    #[test]
+    fn rename_function_rejects_existing_name() {
+        let mut module = load_module(TWO_FUNCTIONS);
+        assert!(matches!(module.rename_function("first", "second"), Err(IRError::DuplicateFunction(_))));
+        assert!(matches!(module.rename_function("missing", "third"), Err(IRError::FunctionNotFound(_))));
+    }
+
+    #[test]
+    fn rename_function_rejects_existing_symbol_or_data() {
+        let mut module = load_module(&RENAMED_REFERENCES.replace(r#""externals": []"#,
+            r#""externals": [{"identifier": "puts", "type": "global"}]"#));
+        let original = module.clone();
+        assert!(matches!(module.rename_function("f", "puts"), Err(IRError::DuplicateSymbol(ref name)) if name == "puts"));
+        assert!(matches!(module.rename_function("f", "ptr"), Err(IRError::DuplicateData(ref name)) if name == "ptr"));
+        assert_eq!(module, original);
+    }
+
+    const CALL_GRAPH: &str = r#"{
+        "globals": [], "externals": [],
+        "types": [{"identifier": 0, "type": []}],
+        "function_declarations": [
+            {"identifier": 0, "name": "callee", "parameters": 0, "vararg": false, "returns": 0},
+            {"identifier": 1, "name": "a", "parameters": 0, "vararg": false, "returns": 0},
+            {"identifier": 2, "name": "b", "parameters": 0, "vararg": false, "returns": 0},
+            {"identifier": 3, "name": "c", "parameters": 0, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "callee", "locals": 0, "body": [{"opcode": "ret"}]},
+            {"identifier": 2, "name": "b", "locals": 0, "body": [
+                {"opcode": "invoke", "arg": {"identifier": 0, "name": "callee"}}, {"opcode": "ret"}
+            ]},
+            {"identifier": 1, "name": "a", "locals": 0, "body": [
+                {"opcode": "invoke", "arg": {"identifier": 0, "name": "callee"}}, {"opcode": "ret"}
+            ]},
+            {"identifier": 3, "name": "c", "locals": 0, "body": [
+                {"opcode": "invoke", "arg": {"identifier": 1, "name": "a"}}, {"opcode": "ret"}
+            ]}
+        ],
+        "data": [], "string_literals": [], "inline_assembly": []
+    }"#;
+
+    #[test]
+    fn callers_of_lists_calling_functions() {
+        let module = load_module(CALL_GRAPH);
+        assert_eq!(module.callers_of(0), vec!["a", "b"]);
+        assert_eq!(module.callers_of(1), vec!["c"]);
+        assert!(module.callers_of(3).is_empty());
+    }
+
+    #[test]
+    fn interned_names_share_storage_with_map_keys() {
+        let module = load_fixture("module.json");
+        let (key, func) = module.functions.get_key_value("main").unwrap();
+        assert!(Rc::ptr_eq(key, &func.shared_name()));
+        assert_eq!(func.name(), "main");
+        let (key, symbol) = module.globals.get_key_value("counter").unwrap();
+        assert!(Rc::ptr_eq(key, &symbol.shared_name()));
+        assert_eq!(symbol.name(), "counter");
+        let (key, data) = module.data.get_key_value("msg").unwrap();
+        assert!(Rc::ptr_eq(key, &data.shared_name()));
+        assert_eq!(module.get_data("msg").map(| data | data.name()), Some("msg"));
+    }
+
+    const SCALAR_PARAMETERS: &str = r#"{
+        "globals": [], "externals": [],
+        "types": [
+            {"identifier": 0, "type": [{"type": "struct", "fields": []}]},
+            {"identifier": 1, "type": [{"type": "long"}]}
+        ],
+        "function_declarations": [
+            {"identifier": 0, "name": "f", "parameters": 1, "vararg": false, "returns": 0}
+        ],
+        "functions": [
+            {"identifier": 0, "name": "f", "locals": 0, "body": [{"opcode": "ret"}]}
+        ],
+        "data": [], "string_literals": [], "inline_assembly": []
+    }"#;
+
+    #[test]
+    fn check_strict_requires_struct_parameter_types() {
+        let module = load_module(SCALAR_PARAMETERS);
+        assert!(module.check().is_ok());
+        assert!(matches!(module.check_strict(), Err(IRError::FunctionDeclTypeNotStruct { decl_id: 0, type_id: 1 })));
+    }
+
+    #[test]
+    fn new_unchecked_defers_validation() {
+        let mut parts = IRModuleBuilder::new();
+        parts.add_type(IRType::new(0, Vec::new())).unwrap()
+            .add_function(IRFunction::new("f", 3, 0, IRBlock::new(vec![IRInstruction::jmp(5)]))).unwrap();
+        let module = IRModule::new_unchecked(parts);
+        let errors = module.validate();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(| err | matches!(err, IRError::InFunction { index: None, .. })));
+        assert!(errors.iter().any(| err | matches!(err, IRError::InFunction { index: Some(0), .. })));
+    }
+
+    #[test]
+    fn validate_collects_every_problem() {
+        let mut parts = IRModuleBuilder::new();
+        parts.add_type(IRType::new(0, Vec::new())).unwrap()
+            .add_function_declaration(IRFunctionDeclaration::new(0, Some("f".to_owned()), 0, false, 4)).unwrap()
+            .add_function(IRFunction::new("f", 0, 0, IRBlock::new(vec![
+                IRInstruction::getlocal(IRTypeRef::new(0, 1)),
+                IRInstruction::pushstring(9),
+                IRInstruction::ret
+            ]))).unwrap()
+            .add_data(IRData::new("ptr", IRDataStorage::Global, 0, vec![
+                IRDataElement::Pointer { base: "missing".to_owned(), offset: 0 }
+            ])).unwrap();
+        let module = IRModule::new_unchecked(parts);
+        let mut errors = module.validate()
+            .into_iter()
+            .map(| err | err.to_string())
+            .collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(errors, vec![
+            "in IR data ptr, element 0: Provided IR symbol missing does not exist in the module",
+            "in IR function declaration 0: Provided IR type identifier 4 does not exist in the module",
+            "in IR function f, instruction 0: Provided IR type index 1 exceeds IR type 0 length (0)",
+            "in IR function f, instruction 1: Provided IR string literal identifier 9 does not exist in the module"
+        ]);
+        assert!(module.validate_strict().len() > errors.len());
+    }
+
+    #[test]
+    fn function_signature_types_resolve_through_declaration() {
+        let module = load_fixture("module.json");
+        assert_eq!(module.function_return_type("main").map(| tp | tp.identifier()), Some(2));
+        assert_eq!(module.function_param_type("main").map(| tp | tp.identifier()), Some(0));
+        assert!(module.function_return_type("missing").is_none());
+    }
+
+    #[test]
+    fn cloned_module_is_equal() {
+        let module = load_fixture("module.json");
+        let copy = module.clone();
+        assert_eq!(copy, module);
+        assert_ne!(copy, load_module(TWO_FUNCTIONS));
+        assert_eq!(IRDataElement::Float64(f64::NAN), IRDataElement::Float64(f64::NAN));
+    }
+
+    #[test]
+    fn cloned_module_still_validates() {
+        let copy = load_fixture("module.json").clone();
+        assert!(copy.validate().is_empty());
+        let inline_asm = copy.get_inline_assembly(0).unwrap().clone();
+        assert!(matches!(inline_asm.get_by_alias("x"), Some(IRInlineAssemblyIndexedAlias::Parameter(0))));
+        assert!(matches!(inline_asm.get_by_alias("l1"), Some(IRInlineAssemblyIndexedAlias::JumpTarget(0))));
+    }
+
+    #[test]
+    fn check_reports_unknown_template_alias() {
+        let param = IRInlineAssemblyParameter::new(0, vec!["x".to_owned()],
+            IRInlineAssemblyParameterClass::Read(IRTypeRef::new(1, 0), 0),
+            IRInlineAssemblyParameterConstraint::Register);
+        let inline_asm = IRInlineAssembly::new(0, false, "mov %[x], %[y]".to_owned(), HashMap::from([(0, param)]),
+            HashSet::new(), HashMap::new()).unwrap();
+        match module_with(0, vec![IRInstruction::ret], Vec::new(), vec![inline_asm]) {
+            Err(IRError::InInlineAssembly { inline_asm_id: 0, error }) =>
+                assert!(matches!(*error, IRError::UnknownTemplateAlias(ref alias) if alias == "y")),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn summary_counts_fixture() {
+        assert_eq!(load_fixture("module.json").summary(), IRModuleSummary {
+            globals: 2,
+            externals: 1,
+            types: 3,
+            function_declarations: 3,
+            functions: 1,
+            data: 2,
+            string_literals: 1,
+            inline_assembly: 1,
+            instructions: 9
+        });
+    }
+
+    #[test]
+    fn resolve_type_ref_entries() {
+        let module = load_fixture("module.json");
+        assert_eq!(module.resolve_type_ref(IRTypeRef::new(1, 0)), Some(&IRTypeEntry::Int32 { alignment: None }));
+        assert_eq!(module.resolve_type_ref(IRTypeRef::new(1, 9)), None);
+        assert_eq!(module.resolve_type_ref(IRTypeRef::new(7, 0)), None);
+    }
+
+    #[test]
+    fn check_reports_global_external_conflict() {
+        let mut builder = IRModuleBuilder::new();
+        builder.add_global(IRSymbol::Global(Rc::from("f"), Default::default())).unwrap()
+            .add_external(IRSymbol::Global(Rc::from("f"), Default::default())).unwrap();
+        match builder.build() {
+            Err(IRError::GlobalExternalConflict(name)) => assert_eq!(name, "f"),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn check_strict_reports_declaration_name_mismatch() {
+        let module = load_module(&SCALAR_PARAMETERS.replace(r#""parameters": 1"#, r#""parameters": 0"#)
+            .replace(r#""name": "f", "locals""#, r#""name": "g", "locals""#));
+        assert!(module.check().is_ok());
+        match module.check_strict() {
+            Err(IRError::FunctionDeclNameMismatch { function, decl_id: 0, decl_name }) => {
+                assert_eq!(function, "g");
+                assert_eq!(decl_name, "f");
+            },
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn validate_strict_reports_oversized_data() {
+        let mut builder = IRModuleBuilder::new();
+        builder.add_type(IRType::new(0, vec![
+                IRTypeEntry::Array { alignment: None, length: 2 },
+                IRTypeEntry::Int32 { alignment: None }
+            ])).unwrap()
+            .add_data(IRData::new("fits", IRDataStorage::Global, 0, vec![
+                IRDataElement::Raw(vec![0; 4]),
+                IRDataElement::Undefined(1)
+            ])).unwrap()
+            .add_data(IRData::new("oversized", IRDataStorage::Global, 0, vec![
+                IRDataElement::Raw(vec![0; 4]),
+                IRDataElement::Integer(1)
+            ])).unwrap()
+            .add_data(IRData::new("opaque", IRDataStorage::Global, 0, vec![
+                IRDataElement::Unknown { class: "quantum".to_owned(), value: serde_json::Value::Null }
+            ])).unwrap();
+        let mut errors = builder.build().unwrap().validate_strict()
+            .into_iter()
+            .map(| err | err.to_string())
+            .collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(errors, vec![
+            "IR data oversized elements occupy 12 bytes exceeding its type size 8"
+        ]);
+    }
+
+    #[test]
+    fn validate_strict_reports_non_finite_floats() {
+        let module = module_with(0, vec![
+                IRInstruction::pushf32(f32::INFINITY),
+                IRInstruction::pushf64(1.5),
+                IRInstruction::pushf64(f64::NAN),
+                IRInstruction::ret
+            ], vec![
+                IRData::new("finite", IRDataStorage::Global, 1, vec![IRDataElement::Float64(2.0)]),
+                IRData::new("nan", IRDataStorage::Global, 1, vec![IRDataElement::Float32(0.0), IRDataElement::Float32(f32::NAN)])
+            ], Vec::new()).unwrap();
+        assert!(module.validate().is_empty());
+        let mut errors = module.validate_strict()
+            .into_iter()
+            .map(| err | err.to_string())
+            .filter(| err | err.ends_with("is not finite"))
+            .collect::<Vec<_>>();
+        errors.sort();
+        assert_eq!(errors, vec![
+            "in IR data nan, element 1: IR floating-point value NaN is not finite",
+            "in IR function f, instruction 0: IR floating-point value inf is not finite",
+            "in IR function f, instruction 2: IR floating-point value NaN is not finite"
+        ]);
+        assert!(module.check_strict().is_err());
+    }
+
+    #[test]
+    fn function_sizes_sort_descending() {
+        let module = load_module(TWO_FUNCTIONS).merge(load_module(THIRD_FUNCTION)).unwrap();
+        assert_eq!(module.function_sizes(), vec![("first", 4), ("second", 2), ("third", 2)]);
+    }
+
+    #[test]
+    fn data_and_globals_filter_by_storage() {
+        let mut builder = IRModuleBuilder::new();
+        builder.add_type(IRType::new(0, vec![IRTypeEntry::Int64 { alignment: None }])).unwrap()
+            .add_data(IRData::new("counter", IRDataStorage::Global, 0, vec![IRDataElement::Integer(0)])).unwrap()
+            .add_data(IRData::new("errno", IRDataStorage::ThreadLocal, 0, vec![IRDataElement::Integer(0)])).unwrap()
+            .add_global(IRSymbol::Global(Rc::from("counter"), IRSymbolAttributes::default())).unwrap()
+            .add_global(IRSymbol::ThreadLocal(Rc::from("errno"), IRSymbolAttributes::default())).unwrap();
+        let module = builder.build().unwrap();
+        assert_eq!(module.data_by_storage(IRDataStorage::Global).map(| data | data.name()).collect::<Vec<_>>(), vec!["counter"]);
+        assert_eq!(module.data_by_storage(IRDataStorage::ThreadLocal).map(| data | data.name()).collect::<Vec<_>>(), vec!["errno"]);
+        assert_eq!(module.globals_by_storage(IRSymbolStorage::ThreadLocal).map(| sym | sym.name()).collect::<Vec<_>>(), vec!["errno"]);
+        assert_eq!(module.globals_by_storage(IRSymbolStorage::Global).map(| sym | sym.name()).collect::<Vec<_>>(), vec!["counter"]);
+    }
+
+    #[test]
+    fn is_leaf_function_detects_calls() {
+        let module = load_module(TWO_FUNCTIONS);
+        assert_eq!(module.is_leaf_function("first"), Some(true));
+        assert_eq!(module.is_leaf_function("second"), Some(false));
+        assert_eq!(module.is_leaf_function("missing"), None);
+    }
+
+    #[test]
+    fn next_free_type_id_skips_existing_ids() {
+        assert_eq!(IRModuleBuilder::new().build().unwrap().next_free_type_id(), 0);
+        let mut builder = IRModuleBuilder::new();
+        builder.add_type(IRType::new(0, Vec::new())).unwrap()
+            .add_type(IRType::new(4, Vec::new())).unwrap();
+        let module = builder.build().unwrap();
+        let mut used = module.used_type_ids().collect::<Vec<_>>();
+        used.sort_unstable();
+        assert_eq!(used, vec![0, 4]);
+        assert_eq!(module.next_free_type_id(), 5);
+        assert_eq!(module.used_function_decl_ids().count(), 0);
+    }
+
+    #[test]
+    fn normalize_reduces_redundancies_once() {
+        let mut module = load_module(r#"{
+            "types": [
+                {"identifier": 0, "type": []},
+                {"identifier": 1, "type": [{"type": "long"}]},
+                {"identifier": 2, "type": [{"type": "long"}]},
+                {"identifier": 3, "type": [{"type": "int"}]}
+            ],
+            "function_declarations": [{"identifier": 0, "name": "f", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "f", "locals": 0, "body": [
+                {"opcode": "getlocal", "arg": {"type": 2, "index": 0}}, {"opcode": "ret"}
+            ]}],
+            "data": [{"identifier": "buf", "storage": "global", "type": 1, "value": [
+                {"class": "undefined", "count": 2}, {"class": "undefined", "count": 3}
+            ]}],
+            "string_literals": [
+                {"id": 0, "public": false, "type": "multibyte", "literal": "unused"},
+                {"id": 1, "public": true, "type": "multibyte", "literal": "exported"}
+            ]
+        }"#);
+        let report = module.normalize(NormalizeOptions::default());
+        assert_eq!(report, NormalizeReport {
+            deduplicated_types: 1,
+            pruned_types: 1,
+            pruned_string_literals: 1,
+            coalesced_undefined: 1
+        });
+        assert_eq!(module.get_function("f").unwrap().body().at(0), Some(&IRInstruction::getlocal(IRTypeRef::new(1, 0))));
+        assert!(module.check().is_ok());
+
+        let normalized = module.clone();
+        assert_eq!(module.normalize(NormalizeOptions::default()), NormalizeReport::default());
+        assert_eq!(module, normalized);
+    }
+
+    #[test]
+    fn validate_strict_rejects_global_inline_asm_jump_targets() {
+        let target = IRInlineAssemblyJumpTarget::new(0, vec!["l".to_owned()], "f".to_owned(), 0);
+        let global_asm = IRInlineAssembly::new(7, true, "jmp %l[l]".to_owned(), HashMap::new(), HashSet::new(),
+            HashMap::from([(0, target)])).unwrap();
+        let module = module_with(0, vec![IRInstruction::ret], Vec::new(), vec![global_asm]).unwrap();
+        assert!(module.validate().is_empty());
+        let errors = module.validate_strict();
+        assert!(errors.iter().any(| err | matches!(err, IRError::GlobalInlineAssemblyJumpTargets(7))), "{:?}", errors);
+
+        let local_asm = inline_asm_jumping_to(0);
+        let module = module_with(0, vec![IRInstruction::ret], Vec::new(), vec![local_asm]).unwrap();
+        assert!(!module.validate_strict().iter().any(| err | matches!(err, IRError::GlobalInlineAssemblyJumpTargets(_))));
+    }
+
+    #[test]
+    fn all_branch_edges_cover_every_function() {
+        let module = load_module(r#"{
+            "globals": [], "externals": [],
+            "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [
+                {"identifier": 0, "name": "first", "parameters": 0, "vararg": false, "returns": 0},
+                {"identifier": 1, "name": "second", "parameters": 0, "vararg": false, "returns": 0}
+            ],
+            "functions": [
+                {"identifier": 1, "name": "second", "locals": 0, "body": [
+                    {"opcode": "jmp", "arg": 1}, {"opcode": "ret"}
+                ]},
+                {"identifier": 0, "name": "first", "locals": 0, "body": [
+                    {"opcode": "push", "arg": 1}, {"opcode": "branch", "arg": 3}, {"opcode": "jmp", "arg": 3}, {"opcode": "ret"}
+                ]}
+            ],
+            "data": [], "string_literals": [], "inline_assembly": []
+        }"#);
+        assert_eq!(module.all_branch_edges(), vec![("first", 1, 3), ("first", 2, 3), ("second", 0, 1)]);
+        assert!(load_module(TWO_FUNCTIONS).all_branch_edges().is_empty());
+    }
+
+    #[test]
+    fn forbidden_branch_target_applies_to_branches_only() {
+        match module_with(0, vec![IRInstruction::jmp(1), IRInstruction::reserved, IRInstruction::ret], Vec::new(), Vec::new()) {
+            Err(IRError::InFunction { error, .. }) =>
+                assert!(matches!(*error, IRError::CodeRefForbiddenTarget { target: 1, ref mnemonic } if mnemonic == "reserved")),
+            other => panic!("unexpected result {:?}", other)
+        }
+
+        let module = module_with(0, vec![IRInstruction::pushlabel(2), IRInstruction::ret, IRInstruction::reserved, IRInstruction::ret],
+            Vec::new(), Vec::new()).unwrap();
+        assert!(module.check().is_ok());
+    }
+
+    #[test]
+    fn get_function_with_declaration_pairs_lookup() {
+        let module = load_module(CALL_GRAPH);
+        let (func, decl) = module.get_function_with_declaration("c").unwrap();
+        assert_eq!(func.name(), "c");
+        assert_eq!(decl.identifier(), 3);
+        assert_eq!(decl.name(), Some("c"));
+        assert!(module.get_function_with_declaration("missing").is_none());
     }
 }
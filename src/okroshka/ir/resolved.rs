@@ -0,0 +1,66 @@
+use crate::okroshka::ir::{
+    IRModule,
+    IRFunction,
+    IRFunctionDeclaration,
+    IRInstruction,
+    IRInstructionArgument,
+    IRStringLiteral,
+    IRType
+};
+
+#[derive(Debug)]
+pub struct IRResolvedInstruction<'a> {
+    pub index: usize,
+    pub instruction: &'a IRInstruction,
+    pub target: Option<&'a IRInstruction>,
+    pub type_ref: Option<&'a IRType>,
+    pub declaration: Option<&'a IRFunctionDeclaration>,
+    pub string_literal: Option<&'a IRStringLiteral>
+}
+
+impl IRModule {
+    pub fn resolved_instructions<'a>(&'a self, func: &'a IRFunction) -> impl Iterator<Item = IRResolvedInstruction<'a>> {
+        func.body().code()
+            .enumerate()
+            .map(move | (index, instruction) | {
+                let mut resolved = IRResolvedInstruction {
+                    index,
+                    instruction,
+                    target: None,
+                    type_ref: None,
+                    declaration: None,
+                    string_literal: None
+                };
+                match instruction.argument() {
+                    IRInstructionArgument::CodeRef(target)
+                        => resolved.target = func.body().at(target),
+                    IRInstructionArgument::TypeRef(typeref)
+                        => resolved.type_ref = self.get_type(typeref.type_id),
+                    IRInstructionArgument::FunctionRef(decl_id, _)
+                        => resolved.declaration = self.get_function_declaration(decl_id),
+                    IRInstructionArgument::String(literal_id)
+                        => resolved.string_literal = self.get_string_literal(literal_id),
+                    _ => ()
+                };
+                resolved
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::okroshka::testing::load_fixture;
+
+    #[test]
+    fn resolved_instructions_annotate_references() {
+        let module = load_fixture("module.json");
+        let func = module.get_function("main").unwrap();
+        let resolved = module.resolved_instructions(func).collect::<Vec<_>>();
+        assert_eq!(resolved.len(), func.body().len());
+        assert_eq!(resolved[3].declaration.and_then(| decl | decl.name()), Some("printf"));
+        assert_eq!(resolved[1].string_literal.map(| literal | literal.identifier()), Some(0));
+        assert_eq!(resolved[2].target.map(| instr | instr.mnemonic()), Some("getlocal"));
+        assert_eq!(resolved[5].type_ref.map(| tp | tp.identifier()), Some(1));
+        assert!(resolved[0].declaration.is_none() && resolved[0].type_ref.is_none());
+    }
+}
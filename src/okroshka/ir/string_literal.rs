@@ -1,15 +1,17 @@
+use std::iter;
 use std::vec::Vec;
+use serde::{Deserialize, Serialize};
 
-use crate::okroshka::ir::IRIdentifier;
+use crate::okroshka::ir::{IRIdentifier, Endianness};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IRStringLiteralContent {
     Multibyte(Vec<u8>),
     Unicode16(Vec<u16>),
     Unicode32(Vec<u32>)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct IRStringLiteral {
     id: IRIdentifier,
     public: bool,
@@ -29,6 +31,10 @@ impl IRStringLiteral {
         self.id
     }
 
+    pub(crate) fn set_identifier(&mut self, id: IRIdentifier) {
+        self.id = id;
+    }
+
     pub fn is_public(&self) -> bool {
         self.public
     }
@@ -36,4 +42,82 @@ impl IRStringLiteral {
     pub fn content(&self) -> &IRStringLiteralContent {
         &self.content
     }
+
+    pub fn as_lossy_string(&self) -> String {
+        match &self.content {
+            IRStringLiteralContent::Multibyte(content)
+                => String::from_utf8_lossy(strip_terminator(content)).into_owned(),
+            IRStringLiteralContent::Unicode16(content)
+                => String::from_utf16_lossy(strip_terminator(content)),
+            IRStringLiteralContent::Unicode32(content) => strip_terminator(content)
+                .iter()
+                .map(| &x | char::from_u32(x).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect()
+        }
+    }
+
+    pub fn to_bytes(&self, endian: Endianness) -> Vec<u8> {
+        match &self.content {
+            IRStringLiteralContent::Multibyte(content) => strip_terminator(content).iter()
+                .copied()
+                .chain(iter::once(0))
+                .collect(),
+            IRStringLiteralContent::Unicode16(content) => strip_terminator(content).iter()
+                .copied()
+                .chain(iter::once(0))
+                .flat_map(| unit | match endian {
+                    Endianness::Little => unit.to_le_bytes(),
+                    Endianness::Big => unit.to_be_bytes()
+                })
+                .collect(),
+            IRStringLiteralContent::Unicode32(content) => strip_terminator(content).iter()
+                .copied()
+                .chain(iter::once(0))
+                .flat_map(| unit | match endian {
+                    Endianness::Little => unit.to_le_bytes(),
+                    Endianness::Big => unit.to_be_bytes()
+                })
+                .collect()
+        }
+    }
+}
+
+fn strip_terminator<T: Default + PartialEq>(content: &[T]) -> &[T] {
+    match content.split_last() {
+        Some((last, rest)) if *last == T::default() => rest,
+        _ => content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_lossy_string_decodes_each_encoding() {
+        let multibyte = IRStringLiteral::new(0, false,
+            IRStringLiteralContent::Multibyte("h\u{e9}llo\0".as_bytes().to_vec()));
+        assert_eq!(multibyte.as_lossy_string(), "h\u{e9}llo");
+        let unicode16 = IRStringLiteral::new(1, false,
+            IRStringLiteralContent::Unicode16("\u{3b1}\u{1f600}\0".encode_utf16().collect()));
+        assert_eq!(unicode16.as_lossy_string(), "\u{3b1}\u{1f600}");
+        let unicode32 = IRStringLiteral::new(2, false,
+            IRStringLiteralContent::Unicode32(vec![0x3b1, 0x1f600, 0]));
+        assert_eq!(unicode32.as_lossy_string(), "\u{3b1}\u{1f600}");
+    }
+
+    #[test]
+    fn to_bytes_serializes_each_encoding() {
+        let multibyte = IRStringLiteral::new(0, false, IRStringLiteralContent::Multibyte(b"ab\0".to_vec()));
+        assert_eq!(multibyte.to_bytes(Endianness::Little), b"ab\0");
+        assert_eq!(multibyte.to_bytes(Endianness::Big), b"ab\0");
+        let unterminated = IRStringLiteral::new(1, false, IRStringLiteralContent::Multibyte(b"ab".to_vec()));
+        assert_eq!(unterminated.to_bytes(Endianness::Little), b"ab\0");
+        let unicode16 = IRStringLiteral::new(2, false, IRStringLiteralContent::Unicode16(vec![0x3b1, 0]));
+        assert_eq!(unicode16.to_bytes(Endianness::Little), vec![0xb1, 0x03, 0, 0]);
+        assert_eq!(unicode16.to_bytes(Endianness::Big), vec![0x03, 0xb1, 0, 0]);
+        let unicode32 = IRStringLiteral::new(3, false, IRStringLiteralContent::Unicode32(vec![0x1f600]));
+        assert_eq!(unicode32.to_bytes(Endianness::Little), vec![0x00, 0xf6, 0x01, 0x00, 0, 0, 0, 0]);
+        assert_eq!(unicode32.to_bytes(Endianness::Big), vec![0x00, 0x01, 0xf6, 0x00, 0, 0, 0, 0]);
+    }
 }
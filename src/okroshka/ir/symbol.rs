@@ -1,14 +1,84 @@
-#[derive(Debug)]
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IRSymbolStorage {
+    Global,
+    ThreadLocal
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum IRSymbolVisibility {
+    #[default]
+    Default,
+    Hidden,
+    Protected
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct IRSymbolAttributes {
+    pub visibility: IRSymbolVisibility,
+    pub weak: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum IRSymbol {
-    Global(String),
-    ThreadLocal(String)
+    Global(Rc<str>, IRSymbolAttributes),
+    ThreadLocal(Rc<str>, IRSymbolAttributes)
 }
 
 impl IRSymbol {
     pub fn name(&self) -> &str {
         match self {
-            IRSymbol::Global(s) => &s,
-            IRSymbol::ThreadLocal(s) => &s
+            IRSymbol::Global(s, _) => s,
+            IRSymbol::ThreadLocal(s, _) => s
         }
     }
+
+    pub fn storage(&self) -> IRSymbolStorage {
+        match self {
+            IRSymbol::Global(..) => IRSymbolStorage::Global,
+            IRSymbol::ThreadLocal(..) => IRSymbolStorage::ThreadLocal
+        }
+    }
+
+    pub fn is_thread_local(&self) -> bool {
+        self.storage() == IRSymbolStorage::ThreadLocal
+    }
+
+    pub fn attributes(&self) -> IRSymbolAttributes {
+        match self {
+            IRSymbol::Global(_, attributes) => *attributes,
+            IRSymbol::ThreadLocal(_, attributes) => *attributes
+        }
+    }
+
+    pub(crate) fn shared_name(&self) -> Rc<str> {
+        match self {
+            IRSymbol::Global(s, _) => s.clone(),
+            IRSymbol::ThreadLocal(s, _) => s.clone()
+        }
+    }
+
+    pub(crate) fn renamed(&self, name: Rc<str>) -> IRSymbol {
+        match self {
+            IRSymbol::Global(_, attributes) => IRSymbol::Global(name, *attributes),
+            IRSymbol::ThreadLocal(_, attributes) => IRSymbol::ThreadLocal(name, *attributes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_of_each_variant() {
+        let global = IRSymbol::Global(Rc::from("g"), IRSymbolAttributes::default());
+        assert_eq!(global.storage(), IRSymbolStorage::Global);
+        assert!(!global.is_thread_local());
+        let thread_local = IRSymbol::ThreadLocal(Rc::from("t"), IRSymbolAttributes::default());
+        assert_eq!(thread_local.storage(), IRSymbolStorage::ThreadLocal);
+        assert!(thread_local.is_thread_local());
+    }
 }
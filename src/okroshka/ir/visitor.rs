@@ -0,0 +1,132 @@
+use crate::okroshka::ir::{
+    IRModule,
+    IRType,
+    IRStringLiteral,
+    IRData,
+    IRInlineAssembly,
+    IRFunction,
+    IRInstruction
+};
+
+pub trait IRVisitor {
+    fn visit_type(&mut self, _tp: &IRType) {}
+
+    fn visit_string_literal(&mut self, _literal: &IRStringLiteral) {}
+
+    fn visit_data(&mut self, _data: &IRData) {}
+
+    fn visit_inline_assembly(&mut self, _inline_asm: &IRInlineAssembly) {}
+
+    fn visit_function(&mut self, _func: &IRFunction) {}
+
+    fn visit_instruction(&mut self, _func: &IRFunction, _index: usize, _instr: &IRInstruction) {}
+}
+
+pub trait IRVisitorMut {
+    fn visit_instruction_mut(&mut self, _instr: &mut IRInstruction) {}
+}
+
+impl IRModule {
+    pub fn accept(&self, visitor: &mut impl IRVisitor) {
+        for tp in self.types_sorted() {
+            visitor.visit_type(tp);
+        }
+
+        for literal in self.string_literals_sorted() {
+            visitor.visit_string_literal(literal);
+        }
+
+        for data in self.data_sorted() {
+            visitor.visit_data(data);
+        }
+
+        let mut inline_assembly = self.inline_assembly().collect::<Vec<_>>();
+        inline_assembly.sort_by_key(| inline_asm | inline_asm.identifier());
+        for inline_asm in inline_assembly {
+            visitor.visit_inline_assembly(inline_asm);
+        }
+
+        for func in self.functions_sorted() {
+            visitor.visit_function(func);
+            for (index, instr) in func.body().code().enumerate() {
+                visitor.visit_instruction(func, index, instr);
+            }
+        }
+    }
+
+    pub fn accept_mut(&mut self, visitor: &mut impl IRVisitorMut) {
+        let mut functions = self.functions_mut().collect::<Vec<_>>();
+        functions.sort_by(| func1, func2 | func1.name().cmp(func2.name()));
+        for func in functions {
+            for instr in func.body_mut().code_mut() {
+                visitor.visit_instruction_mut(instr);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::testing::{load_fixture, load_module};
+
+    #[derive(Default)]
+    struct CountingVisitor {
+        types: usize,
+        functions: usize,
+        instructions: usize
+    }
+
+    impl IRVisitor for CountingVisitor {
+        fn visit_type(&mut self, _tp: &IRType) {
+            self.types += 1;
+        }
+
+        fn visit_function(&mut self, _func: &IRFunction) {
+            self.functions += 1;
+        }
+
+        fn visit_instruction(&mut self, func: &IRFunction, index: usize, instr: &IRInstruction) {
+            assert_eq!(func.body().at(index), Some(instr));
+            self.instructions += 1;
+        }
+    }
+
+    #[test]
+    fn accept_visits_every_instruction() {
+        let module = load_fixture("module.json");
+        let mut visitor = CountingVisitor::default();
+        module.accept(&mut visitor);
+        assert_eq!(visitor.types, 3);
+        assert_eq!(visitor.functions, 1);
+        assert_eq!(visitor.instructions, 9);
+    }
+
+    struct AddReplacingVisitor;
+
+    impl IRVisitorMut for AddReplacingVisitor {
+        fn visit_instruction_mut(&mut self, instr: &mut IRInstruction) {
+            match instr {
+                IRInstruction::iadd => *instr = IRInstruction::isub,
+                IRInstruction::iadd1(value) => *instr = IRInstruction::iadd1(value.wrapping_neg()),
+                _ => ()
+            }
+        }
+    }
+
+    #[test]
+    fn accept_mut_replaces_instructions() {
+        let mut module = load_module(r#"{"globals": [], "externals": [], "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "foo", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "foo", "locals": 0, "body": [
+                {"opcode": "push", "arg": 1}, {"opcode": "push", "arg": 2}, {"opcode": "iadd"},
+                {"opcode": "iadd1", "arg": 3}, {"opcode": "ret"}]}],
+            "data": [], "string_literals": [], "inline_assembly": []}"#);
+        module.accept_mut(&mut AddReplacingVisitor);
+        let body = module.get_function("foo").unwrap().body();
+        assert_eq!(body.at(2), Some(&IRInstruction::isub));
+        assert_eq!(body.at(3), Some(&IRInstruction::iadd1(-3)));
+        assert!(!body.code().any(| instr | matches!(instr, IRInstruction::iadd)));
+        assert!(module.validate().is_empty());
+    }
+}
@@ -0,0 +1,410 @@
+use std::io;
+use serde::{Deserialize, Serialize};
+use serde::de::Error;
+
+use crate::okroshka::ir::{
+    IRSymbol,
+    IRSymbolAttributes,
+    IRIdentifier,
+    IRBlock,
+    IRType,
+    IRTypeEntry,
+    IRFunctionDeclaration,
+    IRFunction,
+    IRData,
+    IRDataElement,
+    IRDataStorage,
+    IRLongDouble,
+    IRStringLiteral,
+    IRStringLiteralContent,
+    IRModule,
+    IRModuleBuilder,
+    IRInlineAssembly,
+    IRInlineAssemblyParameter,
+    IRInlineAssemblyJumpTarget,
+    IRInstruction
+};
+use crate::okroshka::ir::instr::IRInstructionDef;
+use crate::okroshka::loader::LoadError;
+
+#[derive(Serialize, Deserialize)]
+struct BinaryInstruction(#[serde(with = "IRInstructionDef")] IRInstruction);
+
+#[derive(Serialize, Deserialize)]
+struct BinarySymbol {
+    name: String,
+    thread_local: bool,
+    attributes: IRSymbolAttributes
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryType {
+    id: IRIdentifier,
+    content: Vec<IRTypeEntry>
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryFunctionDeclaration {
+    id: IRIdentifier,
+    name: Option<String>,
+    params: IRIdentifier,
+    vararg: bool,
+    result: IRIdentifier
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryFunction {
+    name: String,
+    declaration: IRIdentifier,
+    locals: IRIdentifier,
+    body: Vec<BinaryInstruction>,
+    metadata: Vec<(usize, String)>
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryDataElement {
+    Undefined(u64),
+    Integer(i64),
+    Float32(f32),
+    Float64(f64),
+    LongDouble(IRLongDouble),
+    String(Vec<u8>),
+    Pointer{ base: String, offset: i64 },
+    StringPointer{base: IRIdentifier, offset: i64},
+    Raw(Vec<u8>),
+    Aggregate,
+    Unknown{class: String, value: String}
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryData {
+    name: String,
+    storage: IRDataStorage,
+    datatype: IRIdentifier,
+    data: Vec<BinaryDataElement>
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryStringLiteral {
+    id: IRIdentifier,
+    public: bool,
+    content: IRStringLiteralContent
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryInlineAssembly {
+    id: IRIdentifier,
+    global: bool,
+    template: String,
+    parameters: Vec<IRInlineAssemblyParameter>,
+    clobbers: Vec<String>,
+    jump_targets: Vec<IRInlineAssemblyJumpTarget>
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryModule {
+    instruction_revision: Option<u64>,
+    opcode_revision: Option<u64>,
+    globals: Vec<BinarySymbol>,
+    externals: Vec<BinarySymbol>,
+    types: Vec<BinaryType>,
+    string_literals: Vec<BinaryStringLiteral>,
+    function_declarations: Vec<BinaryFunctionDeclaration>,
+    functions: Vec<BinaryFunction>,
+    data: Vec<BinaryData>,
+    inline_assembly: Vec<BinaryInlineAssembly>
+}
+
+struct Fnv1aWriter {
+    hash: u64
+}
+
+impl io::Write for Fnv1aWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.hash ^= byte as u64;
+            self.hash = self.hash.wrapping_mul(0x100000001b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn sorted_by_key<T, K: Ord>(mut items: Vec<T>, key: impl Fn(&T) -> K) -> Vec<T> {
+    items.sort_by_key(key);
+    items
+}
+
+impl BinarySymbol {
+    fn from_symbol(sym: &IRSymbol) -> BinarySymbol {
+        BinarySymbol {
+            name: sym.name().to_owned(),
+            thread_local: sym.is_thread_local(),
+            attributes: sym.attributes()
+        }
+    }
+
+    fn into_symbol(self) -> IRSymbol {
+        if self.thread_local {
+            IRSymbol::ThreadLocal(self.name.into(), self.attributes)
+        } else {
+            IRSymbol::Global(self.name.into(), self.attributes)
+        }
+    }
+}
+
+impl BinaryDataElement {
+    fn from_element(element: &IRDataElement) -> BinaryDataElement {
+        match element {
+            IRDataElement::Undefined(count) => BinaryDataElement::Undefined(*count),
+            IRDataElement::Integer(x) => BinaryDataElement::Integer(*x),
+            IRDataElement::Float32(x) => BinaryDataElement::Float32(*x),
+            IRDataElement::Float64(x) => BinaryDataElement::Float64(*x),
+            IRDataElement::LongDouble(x) => BinaryDataElement::LongDouble(x.clone()),
+            IRDataElement::String(content) => BinaryDataElement::String(content.clone()),
+            IRDataElement::Pointer { base, offset } => BinaryDataElement::Pointer { base: base.clone(), offset: *offset },
+            IRDataElement::StringPointer { base, offset } => BinaryDataElement::StringPointer { base: *base, offset: *offset },
+            IRDataElement::Raw(content) => BinaryDataElement::Raw(content.clone()),
+            IRDataElement::Aggregate => BinaryDataElement::Aggregate,
+            IRDataElement::Unknown { class, value } => BinaryDataElement::Unknown { class: class.clone(), value: value.to_string() }
+        }
+    }
+
+    fn into_element<E: Error>(self) -> Result<IRDataElement, E> {
+        Ok(match self {
+            BinaryDataElement::Undefined(count) => IRDataElement::Undefined(count),
+            BinaryDataElement::Integer(x) => IRDataElement::Integer(x),
+            BinaryDataElement::Float32(x) => IRDataElement::Float32(x),
+            BinaryDataElement::Float64(x) => IRDataElement::Float64(x),
+            BinaryDataElement::LongDouble(x) => IRDataElement::LongDouble(x),
+            BinaryDataElement::String(content) => IRDataElement::String(content),
+            BinaryDataElement::Pointer { base, offset } => IRDataElement::Pointer { base, offset },
+            BinaryDataElement::StringPointer { base, offset } => IRDataElement::StringPointer { base, offset },
+            BinaryDataElement::Raw(content) => IRDataElement::Raw(content),
+            BinaryDataElement::Aggregate => IRDataElement::Aggregate,
+            BinaryDataElement::Unknown { class, value } => IRDataElement::Unknown {
+                class,
+                value: serde_json::from_str(&value).map_err(E::custom)?
+            }
+        })
+    }
+}
+
+impl BinaryModule {
+    fn from_module(module: &IRModule) -> BinaryModule {
+        BinaryModule {
+            instruction_revision: IRInstruction::revision(),
+            opcode_revision: module.opcode_revision(),
+            globals: sorted_by_key(module.globals().map(BinarySymbol::from_symbol).collect(),
+                | sym | sym.name.clone()),
+            externals: sorted_by_key(module.externals().map(BinarySymbol::from_symbol).collect(),
+                | sym | sym.name.clone()),
+            types: sorted_by_key(module.types()
+                .map(| tp | BinaryType {
+                    id: tp.identifier(),
+                    content: tp.type_entries().cloned().collect()
+                })
+                .collect(), | tp | tp.id),
+            string_literals: sorted_by_key(module.string_literals()
+                .map(| literal | BinaryStringLiteral {
+                    id: literal.identifier(),
+                    public: literal.is_public(),
+                    content: literal.content().clone()
+                })
+                .collect(), | literal | literal.id),
+            function_declarations: sorted_by_key(module.function_declarations()
+                .map(| decl | BinaryFunctionDeclaration {
+                    id: decl.identifier(),
+                    name: decl.name().map(| name | name.to_owned()),
+                    params: decl.params_type(),
+                    vararg: decl.params_vararg(),
+                    result: decl.return_type()
+                })
+                .collect(), | decl | decl.id),
+            functions: sorted_by_key(module.functions()
+                .map(| func | BinaryFunction {
+                    name: func.name().to_owned(),
+                    declaration: func.declaration_id(),
+                    locals: func.locals_type(),
+                    body: func.body().code()
+                        .map(| instr | BinaryInstruction(instr.clone()))
+                        .collect(),
+                    metadata: func.body().metadata()
+                        .map(| (index, metadata) | (index, metadata.to_string()))
+                        .collect()
+                })
+                .collect(), | func | func.name.clone()),
+            data: sorted_by_key(module.data()
+                .map(| data | BinaryData {
+                    name: data.name().to_owned(),
+                    storage: data.data_storage(),
+                    datatype: data.data_type(),
+                    data: data.elements().map(BinaryDataElement::from_element).collect()
+                })
+                .collect(), | data | data.name.clone()),
+            inline_assembly: sorted_by_key(module.inline_assembly()
+                .map(| inline_asm | BinaryInlineAssembly {
+                    id: inline_asm.identifier(),
+                    global: inline_asm.is_global(),
+                    template: inline_asm.template().to_owned(),
+                    parameters: sorted_by_key(inline_asm.parameters().cloned().collect(),
+                        | param | param.identifier()),
+                    clobbers: inline_asm.clobbers_sorted().into_iter()
+                        .map(| clobber | clobber.to_owned())
+                        .collect(),
+                    jump_targets: sorted_by_key(inline_asm.jump_targets().cloned().collect(),
+                        | target | target.identifier())
+                })
+                .collect(), | inline_asm | inline_asm.id)
+        }
+    }
+
+    fn into_module<E: Error>(self) -> Result<IRModule, E> {
+        if self.instruction_revision != IRInstruction::revision() {
+            Err(E::custom("IR module binary was written with a different opcode revision"))?
+        }
+
+        let mut builder = IRModuleBuilder::new();
+        for sym in self.globals {
+            builder.add_global(sym.into_symbol()).map_err(E::custom)?;
+        }
+        for sym in self.externals {
+            builder.add_external(sym.into_symbol()).map_err(E::custom)?;
+        }
+        for tp in self.types {
+            builder.add_type(IRType::new(tp.id, tp.content)).map_err(E::custom)?;
+        }
+        for literal in self.string_literals {
+            builder.add_string_literal(IRStringLiteral::new(literal.id, literal.public, literal.content))
+                .map_err(E::custom)?;
+        }
+        for decl in self.function_declarations {
+            builder.add_function_declaration(IRFunctionDeclaration::new(decl.id, decl.name, decl.params, decl.vararg, decl.result))
+                .map_err(E::custom)?;
+        }
+        for func in self.functions {
+            let mut body = IRBlock::new(func.body.into_iter().map(| instr | instr.0).collect());
+            for (index, metadata) in func.metadata {
+                body.set_metadata(index, serde_json::from_str(&metadata).map_err(E::custom)?);
+            }
+            builder.add_function(IRFunction::new(func.name, func.declaration, func.locals, body))
+                .map_err(E::custom)?;
+        }
+        for data in self.data {
+            let elements = data.data.into_iter()
+                .map(| element | element.into_element())
+                .collect::<Result<Vec<_>, E>>()?;
+            builder.add_data(IRData::new(data.name, data.storage, data.datatype, elements))
+                .map_err(E::custom)?;
+        }
+        for inline_asm in self.inline_assembly {
+            let inline_asm = IRInlineAssembly::new(inline_asm.id, inline_asm.global, inline_asm.template,
+                    inline_asm.parameters.into_iter().map(| param | (param.identifier(), param)).collect(),
+                    inline_asm.clobbers.into_iter().collect(),
+                    inline_asm.jump_targets.into_iter().map(| target | (target.identifier(), target)).collect())
+                .map_err(E::custom)?;
+            builder.add_inline_assembly(inline_asm).map_err(E::custom)?;
+        }
+        let mut module = builder.build().map_err(E::custom)?;
+        module.set_opcode_revision(self.opcode_revision);
+        Ok(module)
+    }
+}
+
+impl IRModule {
+    pub fn write_bincode(&self, w: &mut impl io::Write) -> Result<(), bincode::Error> {
+        bincode::serialize_into(w, &BinaryModule::from_module(self))
+    }
+
+    pub fn content_hash(&self) -> Result<u64, bincode::Error> {
+        let mut writer = Fnv1aWriter {
+            hash: 0xcbf29ce484222325
+        };
+        bincode::serialize_into(&mut writer, &BinaryModule::from_module(self))?;
+        Ok(writer.hash)
+    }
+
+    pub fn read_bincode(r: &mut impl io::Read) -> Result<IRModule, LoadError> {
+        bincode::deserialize_from::<_, BinaryModule>(r)
+            .and_then(| module | module.into_module())
+            .map_err(LoadError::Bincode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::ir::IRError;
+    use crate::okroshka::testing::{load_fixture, load_module};
+
+    fn reload(binary: &BinaryModule) -> Result<IRModule, LoadError> {
+        let mut buffer = Vec::new();
+        bincode::serialize_into(&mut buffer, binary).unwrap();
+        IRModule::read_bincode(&mut buffer.as_slice())
+    }
+
+    #[test]
+    fn bincode_round_trip_matches_json() {
+        let module = load_fixture("module.json");
+        let mut buffer = Vec::new();
+        module.write_bincode(&mut buffer).unwrap();
+        let reloaded = IRModule::read_bincode(&mut buffer.as_slice()).unwrap();
+        assert_eq!(reloaded, module);
+    }
+
+    #[test]
+    fn bincode_round_trip_preserves_metadata() {
+        let module = load_module(r#"{
+            "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "main", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "main", "locals": 0, "body": [
+                {"opcode": "ret", "metadata": {"source_location": {"file": "main.c", "line": 1, "column": 1}}}
+            ]}]
+        }"#);
+        let mut buffer = Vec::new();
+        module.write_bincode(&mut buffer).unwrap();
+        let reloaded = IRModule::read_bincode(&mut buffer.as_slice()).unwrap();
+        assert_eq!(reloaded, module);
+        assert!(reloaded.get_function("main").unwrap().source_location(0).is_some());
+    }
+
+    #[test]
+    fn bincode_rejects_duplicate_entries() {
+        let module = load_fixture("module.json");
+        let mut binary = BinaryModule::from_module(&module);
+        binary.types.push(BinaryType {
+            id: binary.types[0].id,
+            content: Vec::new()
+        });
+        let message = reload(&binary).unwrap_err().to_string();
+        assert!(message.contains(&IRError::DuplicateTypeId(binary.types[0].id).to_string()), "{}", message);
+
+        let mut binary = BinaryModule::from_module(&module);
+        binary.functions.push(BinaryFunction {
+            name: "main".to_owned(),
+            declaration: 0,
+            locals: 0,
+            body: Vec::new(),
+            metadata: Vec::new()
+        });
+        let message = reload(&binary).unwrap_err().to_string();
+        assert!(message.contains(&IRError::DuplicateFunction("main".to_owned()).to_string()), "{}", message);
+    }
+
+    #[test]
+    fn content_hash_ignores_input_order() {
+        let module = | functions: &str | load_module(&format!(r#"{{
+            "types": [{{"identifier": 0, "type": []}}],
+            "function_declarations": [{{"identifier": 0, "name": null, "parameters": 0, "vararg": false, "returns": 0}}],
+            "functions": [{}]
+        }}"#, functions));
+        let foo = r#"{"identifier": 0, "name": "foo", "locals": 0, "body": [{"opcode": "push", "arg": 1}, {"opcode": "ret"}]}"#;
+        let bar = r#"{"identifier": 0, "name": "bar", "locals": 0, "body": [{"opcode": "ret"}]}"#;
+        let hash = module(&format!("{}, {}", foo, bar)).content_hash().unwrap();
+        assert_eq!(module(&format!("{}, {}", bar, foo)).content_hash().unwrap(), hash);
+        assert_ne!(module(foo).content_hash().unwrap(), hash);
+    }
+}
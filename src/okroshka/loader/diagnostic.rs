@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+
+use crate::okroshka::ir::IRModule;
+use crate::okroshka::loader::{IRModuleLoader, LoadError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticSeverity {
+    Note,
+    Warning
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: DiagnosticSeverity,
+    pub message: String
+}
+
+impl Diagnostic {
+    fn warning(message: String) -> Diagnostic {
+        Diagnostic {
+            severity: DiagnosticSeverity::Warning,
+            message
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiagnosticSeverity::Note => write!(f, "note"),
+            DiagnosticSeverity::Warning => write!(f, "warning")
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+impl IRModule {
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for literal_id in self.unused_string_literals() {
+            let message = match self.get_string_literal(literal_id) {
+                Some(literal) if literal.is_public() => format!("IR string literal {} is public but never referenced", literal_id),
+                _ => format!("IR string literal {} is never referenced", literal_id)
+            };
+            diagnostics.push(Diagnostic::warning(message));
+        }
+
+        for func in self.functions_sorted() {
+            if func.is_empty() {
+                diagnostics.push(Diagnostic::warning(format!("IR function {} has an empty body", func.name())));
+            }
+
+            match self.get_function_declaration(func.declaration_id()).and_then(| decl | decl.name()) {
+                Some(decl_name) if decl_name != func.name() =>
+                    diagnostics.push(Diagnostic::warning(format!("IR function {} does not match the name {} of its declaration {}",
+                        func.name(), decl_name, func.declaration_id()))),
+                _ => ()
+            }
+        }
+        diagnostics
+    }
+
+    pub fn load_with_diagnostics<R: io::Read>(reader: R) -> Result<(IRModule, Vec<Diagnostic>), LoadError> {
+        IRModuleLoader::default().load_with_diagnostics(reader)
+    }
+}
+
+impl IRModuleLoader {
+    pub fn load_with_diagnostics<R: io::Read>(self, reader: R) -> Result<(IRModule, Vec<Diagnostic>), LoadError> {
+        let module = self.from_json_reader(reader)?;
+        let diagnostics = module.diagnostics();
+        Ok((module, diagnostics))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNUSED_LITERAL: &str = r#"{
+        "types": [{"identifier": 0, "type": []}],
+        "function_declarations": [{"identifier": 0, "name": "f", "parameters": 0, "vararg": false, "returns": 0}],
+        "functions": [{"identifier": 0, "name": "f", "locals": 0, "body": [{"opcode": "ret"}]}],
+        "string_literals": [{"id": 0, "public": PUBLIC, "type": "multibyte", "literal": "unused"}]
+    }"#;
+
+    #[test]
+    fn unused_literal_is_a_warning() {
+        for (public, message) in [("false", "IR string literal 0 is never referenced"),
+                                  ("true", "IR string literal 0 is public but never referenced")] {
+            let json = UNUSED_LITERAL.replace("PUBLIC", public);
+            let (module, diagnostics) = IRModule::load_with_diagnostics(json.as_bytes()).unwrap();
+            assert!(module.get_function("f").is_some());
+            assert_eq!(diagnostics, vec![Diagnostic {
+                severity: DiagnosticSeverity::Warning,
+                message: message.to_owned()
+            }]);
+        }
+    }
+
+    #[test]
+    fn fatal_errors_still_fail() {
+        let json = UNUSED_LITERAL.replace("PUBLIC", "false").replace(r#""locals": 0"#, r#""locals": 9"#);
+        assert!(IRModule::load_with_diagnostics(json.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum LoadError {
+    IO(Option<PathBuf>, io::Error),
+    Deserialize(Option<PathBuf>, serde_json::Error),
+    MessagePack(rmp_serde::decode::Error),
+    Bincode(bincode::Error)
+}
+
+impl LoadError {
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            LoadError::IO(path, _) => path.as_ref(),
+            LoadError::Deserialize(path, _) => path.as_ref(),
+            LoadError::MessagePack(_) => None,
+            LoadError::Bincode(_) => None
+        }
+    }
+
+    pub(crate) fn from_json(path: Option<PathBuf>, err: serde_json::Error) -> LoadError {
+        if err.is_io() {
+            LoadError::IO(path, err.into())
+        } else {
+            LoadError::Deserialize(path, err)
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::IO(Some(path), err) => write!(f, "unable to read {}: {}", path.display(), err),
+            LoadError::IO(None, err) => write!(f, "unable to read IR module: {}", err),
+            LoadError::Deserialize(Some(path), err) => write!(f, "unable to load IR module from {}: {}", path.display(), err),
+            LoadError::Deserialize(None, err) => write!(f, "unable to load IR module: {}", err),
+            LoadError::MessagePack(err) => write!(f, "unable to load IR module from MessagePack: {}", err),
+            LoadError::Bincode(err) => write!(f, "unable to load IR module from bincode: {}", err)
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::IO(_, err) => Some(err),
+            LoadError::Deserialize(_, err) => Some(err),
+            LoadError::MessagePack(err) => Some(err),
+            LoadError::Bincode(err) => Some(err)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StreamError<E> {
+    Load(LoadError),
+    Callback(E)
+}
+
+impl<E: fmt::Display> fmt::Display for StreamError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Load(err) => write!(f, "{}", err),
+            StreamError::Callback(err) => write!(f, "function callback failed: {}", err)
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for StreamError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Load(err) => Some(err),
+            StreamError::Callback(err) => Some(err)
+        }
+    }
+}
@@ -1,11 +1,22 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::num::{IntErrorKind, ParseIntError};
+use std::path::Path;
+use std::str::FromStr;
 use std::vec::Vec;
 use serde::{Deserialize, Deserializer};
-use serde::de::{Error, IntoDeserializer};
+use serde::de::{DeserializeSeed, Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use serde_json::Value;
+use serde_json::value::RawValue;
 
 use crate::okroshka::ir::{
     IRSymbol,
+    IRSymbolAttributes,
+    IRSymbolVisibility,
     IRIdentifier,
     IRBlock,
     IRType,
@@ -16,8 +27,10 @@ use crate::okroshka::ir::{
     IRData,
     IRDataElement,
     IRDataStorage,
+    IRLongDouble,
     IRStringLiteral,
     IRModule,
+    IRModuleBuilder,
     IRStringLiteralContent,
     IRInlineAssembly,
     IRInlineAssemblyParameterClass,
@@ -26,159 +39,242 @@ use crate::okroshka::ir::{
     IRInlineAssemblyParameter,
     IRInlineAssemblyJumpTarget,
     IRInstructionMemFlags,
-    IRInstruction
+    IRMemoryOrder,
+    IRInstruction,
+    OpcodeArgClass,
+    IRError
 };
+use crate::okroshka::loader::{LoadError, StreamError};
 
 include!(concat!(env!("OUT_DIR"), "/instr-loader.rs"));
 
+pub const DEFAULT_MAX_TYPE_DEPTH: usize = 256;
+
 impl<'de> Deserialize<'de> for IRSymbol {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         let value = serde_json::Value::deserialize(deserializer)?;
+        if let serde_json::Value::String(identifier) = value {
+            return Ok(IRSymbol::Global(identifier.into(), IRSymbolAttributes::default()));
+        }
         let identifier = value.get("identifier")
-            .map(| val | val.as_str())
-            .flatten()
+            .and_then(| val | val.as_str())
             .ok_or(D::Error::custom("unable to deserialize IR symbol identifier"))?;
-        match value.get("type").map(| val | val.as_str()).flatten() {
-            Some("global") => Ok(IRSymbol::Global(identifier.to_owned())),
-            Some("thread_local") => Ok(IRSymbol::ThreadLocal(identifier.to_owned())),
+        let visibility = match value.get("visibility") {
+            None | Some(serde_json::Value::Null) => IRSymbolVisibility::Default,
+            Some(visibility) => match visibility.as_str() {
+                Some("default") => IRSymbolVisibility::Default,
+                Some("hidden") => IRSymbolVisibility::Hidden,
+                Some("protected") => IRSymbolVisibility::Protected,
+                _ => Err(D::Error::custom("unable to deserialize IR symbol visibility"))?
+            }
+        };
+        let weak = match value.get("weak") {
+            None | Some(serde_json::Value::Null) => false,
+            Some(weak) => weak.as_bool()
+                .ok_or(D::Error::custom("unable to deserialize IR symbol weak attribute"))?
+        };
+        let attributes = IRSymbolAttributes {
+            visibility,
+            weak
+        };
+        match value.get("type").and_then(| val | val.as_str()) {
+            Some("global") => Ok(IRSymbol::Global(identifier.into(), attributes)),
+            Some("thread_local") => Ok(IRSymbol::ThreadLocal(identifier.into(), attributes)),
             _ => Err(D::Error::custom("unable to deserialize IR symbol type"))
         }
     }
 }
 
-fn deserialize_instr_u64<'de, D>(value: &Value) -> Result<u64, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_integer_string<E, T>(value: &str, type_name: &str) -> Result<T, E>
+where
+    E: Error,
+    T: FromStr<Err = ParseIntError> {
+    value.parse::<T>()
+        .map_err(| err | match err.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+                => E::custom(format!("IR instruction argument {} is out of {} range", value, type_name)),
+            _ => E::custom("unable to deserialize IR instruction argument")
+        })
+}
+
+fn deserialize_instr_u64<E>(value: &Value) -> Result<u64, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Number(x)) if x.is_u64() =>
             Ok(x.as_u64().unwrap()),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        Some(serde_json::Value::String(x)) =>
+            deserialize_instr_integer_string::<E, u64>(x, "u64"),
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_usize<'de, D>(value: &Value) -> Result<usize, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_usize<E>(value: &Value) -> Result<usize, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Number(x)) if x.is_u64() =>
             Ok(x.as_u64().unwrap() as usize),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_i64<'de, D>(value: &Value) -> Result<i64, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_i64<E>(value: &Value) -> Result<i64, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Number(x)) if x.is_i64() =>
             Ok(x.as_i64().unwrap()),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        Some(serde_json::Value::String(x)) =>
+            deserialize_instr_integer_string::<E, i64>(x, "i64"),
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_f64<'de, D>(value: &Value) -> Result<f64, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_f64<E>(value: &Value) -> Result<f64, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Number(x)) if x.is_f64() =>
             Ok(x.as_f64().unwrap()),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_f32<'de, D>(value: &Value) -> Result<f32, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_f32<E>(value: &Value) -> Result<f32, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Number(x)) if x.is_f64() =>
             Ok(x.as_f64().unwrap() as f32),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_u32<'de, D>(value: &Value) -> Result<(u32, u32), D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_u32<E>(value: &Value) -> Result<(u32, u32), E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Array(x)) if x.len() == 2 && x[0].is_u64() && x[1].is_u64() => {
-            let type_id = x[0].as_u64().ok_or(D::Error::custom("unable to deserialize IR instruction argument"))?;
-            let type_index = x[1].as_u64().ok_or(D::Error::custom("unable to deserialize IR instruction argument"))?;
+            let type_id = x[0].as_u64().ok_or(E::custom("unable to deserialize IR instruction argument"))?;
+            let type_index = x[1].as_u64().ok_or(E::custom("unable to deserialize IR instruction argument"))?;
             Ok((type_id as u32, type_index as u32))
         },
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_bool<'de, D>(value: &Value) -> Result<bool, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_bool<E>(value: &Value) -> Result<bool, E>
+where E: Error {
     match value.get("arg") {
         Some(serde_json::Value::Bool(x)) => Ok(*x),
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_typeref<'de, D>(value: &Value) -> Result<IRTypeRef, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_typeref<E>(value: &Value) -> Result<IRTypeRef, E>
+where E: Error {
     match value.get("arg") {
         Some(x) => {
             let type_id = x.get("type")
-                .map(| val | val.as_u64())
-                .flatten()
-                .ok_or(D::Error::custom("unable to deserialize IR instruction argument"))?;
+                .and_then(| val | val.as_u64())
+                .ok_or(E::custom("unable to deserialize IR instruction argument"))?;
             let type_index = x.get("index")
-                .map(| val | val.as_u64())
-                .flatten()
-                .ok_or(D::Error::custom("unable to deserialize IR instruction argument"))? as usize;
+                .and_then(| val | val.as_u64())
+                .ok_or(E::custom("unable to deserialize IR instruction argument"))? as usize;
             Ok(IRTypeRef::new(type_id, type_index))
         },
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_identifier<'de, D>(value: &Value) -> Result<String, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_identifier<E>(value: &Value) -> Result<String, E>
+where E: Error {
     match value.get("arg") {
         Some(x) if x.get("data").is_some() => {
             let identifier = x.get("data")
-                .map(| val | val.as_str())
-                .flatten()
-                .ok_or(D::Error::custom("unable to deserialize IR instruction argument"))?.to_owned();
+                .and_then(| val | val.as_str())
+                .ok_or(E::custom("unable to deserialize IR instruction argument"))?.to_owned();
             Ok(identifier)
         },
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_funcref<'de, D>(value: &Value) -> Result<(u64, Option<String>), D::Error>
-where D: Deserializer<'de> {
+fn deserialize_instr_funcref<E>(value: &Value) -> Result<(u64, Option<String>), E>
+where E: Error {
     match value.get("arg") {
         Some(x) if x.get("identifier").is_some() => {
             let identifier = x.get("identifier")
-                .map(| val | val.as_u64())
-                .flatten()
-                .ok_or(D::Error::custom("unable to deserialize IR instruction argument"))?;
+                .and_then(| val | val.as_u64())
+                .ok_or(E::custom("unable to deserialize IR instruction argument"))?;
             let name: Option<String> = match x.get("name") {
                 Some(serde_json::Value::String(x)) => Some(x.to_owned()),
                 Some(serde_json::Value::Null) => None,
                 None => None,
-                _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))?
+                _ => Err(E::custom("unable to deserialize IR instruction argument"))?
             };
             Ok((identifier, name))
         },
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
     }
 }
 
-fn deserialize_instr_memflags<'de, D>(value: &Value) -> Result<IRInstructionMemFlags, D::Error>
-where D: Deserializer<'de> {
+fn deserialize_memory_order<E>(order: &str) -> Result<IRMemoryOrder, E>
+where E: Error {
+    match order {
+        "relaxed" => Ok(IRMemoryOrder::Relaxed),
+        "acquire" => Ok(IRMemoryOrder::Acquire),
+        "release" => Ok(IRMemoryOrder::Release),
+        "acq_rel" => Ok(IRMemoryOrder::AcqRel),
+        "seq_cst" => Ok(IRMemoryOrder::SeqCst),
+        _ => Err(E::custom("unable to deserialize IR instruction memory order"))
+    }
+}
+
+fn deserialize_instr_memflags<E>(value: &Value) -> Result<IRInstructionMemFlags, E>
+where E: Error {
     match value.get("memory_flags") {
         Some(x) if x.is_object() => {
             let volatile_flag = x.get("volatile")
-                .map(| val | val.as_bool())
-                .flatten()
-                .ok_or(D::Error::custom("unable to deserialize IR instruction memory flags"))?;
+                .and_then(| val | val.as_bool())
+                .ok_or(E::custom("unable to deserialize IR instruction memory flags"))?;
+            let atomic = match x.get("atomic").or_else(|| x.get("memory_order")) {
+                None | Some(serde_json::Value::Null) => None,
+                Some(serde_json::Value::String(order)) => Some(deserialize_memory_order::<E>(order)?),
+                Some(order) if order.is_object() => Some(deserialize_memory_order::<E>(order.get("memory_order")
+                    .and_then(| val | val.as_str())
+                    .ok_or(E::custom("unable to deserialize IR instruction memory order"))?)?),
+                _ => Err(E::custom("unable to deserialize IR instruction memory order"))?
+            };
             Ok(IRInstructionMemFlags {
-                volatile: volatile_flag
+                volatile: volatile_flag,
+                atomic
             })
         },
-        _ => Err(D::Error::custom("unable to deserialize IR instruction argument"))
+        _ => Err(E::custom("unable to deserialize IR instruction argument"))
+    }
+}
+
+impl IRInstruction {
+    fn deserialize_value<E>(value: &serde_json::Value, strict_arguments: bool, strict_opcode_codes: bool) -> Result<Self, E>
+    where
+        E: Error,
+    {
+        let opcode = value.get("opcode")
+            .and_then(| val | val.as_str())
+            .ok_or(E::custom("unable to deserialize IR instruction opcode"))?;
+        if strict_arguments && value.get("arg").is_some() &&
+            IRInstruction::class_of_mnemonic(opcode) == Some(OpcodeArgClass::None) {
+            Err(E::custom(format!("IR instruction {} does not take an argument", opcode)))?
+        }
+        if let Some(code) = value.get("code").filter(| _ | strict_opcode_codes) {
+            let code = code.as_u64()
+                .ok_or(E::custom("unable to deserialize IR instruction opcode code"))?;
+            match IRInstruction::code_of_mnemonic(opcode) {
+                Some(expected) if expected != code =>
+                    Err(E::custom(format!("IR instruction {} opcode code {} does not match expected code {}", opcode, code, expected)))?,
+                _ => ()
+            }
+        }
+        deserialize_instruction(opcode, value)
     }
 }
 
@@ -188,11 +284,125 @@ impl<'de> Deserialize<'de> for IRInstruction {
         D: Deserializer<'de>,
     {
         let value = serde_json::Value::deserialize(deserializer)?;
-        let opcode = value.get("opcode")
-            .map(| val | val.as_str())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR instruction opcode"))?;
-        deserialize_instruction::<D>(opcode, &value)
+        IRInstruction::deserialize_value(&value, false, false)
+    }
+}
+
+struct IRElementArrayVisitor<T, F> {
+    error: &'static str,
+    element: F,
+    element_type: PhantomData<T>
+}
+
+impl<T, F> IRElementArrayVisitor<T, F> {
+    fn new(error: &'static str, element: F) -> IRElementArrayVisitor<T, F> {
+        IRElementArrayVisitor {
+            error,
+            element,
+            element_type: PhantomData
+        }
+    }
+
+    fn invalid<E>(self) -> Result<Result<(), String>, E> {
+        Ok(Err(self.error.to_owned()))
+    }
+}
+
+impl<'de, T, F> Visitor<'de> for IRElementArrayVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(usize, T) -> Result<(), String>
+{
+    type Value = Result<(), String>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an array")
+    }
+
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut index = 0;
+        while let Some(value) = seq.next_element::<T>()? {
+            if let Err(err) = (self.element)(index, value) {
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                return Ok(Err(err));
+            }
+            index += 1;
+        }
+        Ok(Ok(()))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        self.invalid()
+    }
+
+    fn visit_bool<E: Error>(self, _: bool) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_i64<E: Error>(self, _: i64) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_u64<E: Error>(self, _: u64) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_f64<E: Error>(self, _: f64) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_str<E: Error>(self, _: &str) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_bytes<E: Error>(self, _: &[u8]) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        self.invalid()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IRBlockSeed {
+    strict_arguments: bool,
+    strict_opcode_codes: bool
+}
+
+impl<'de> DeserializeSeed<'de> for IRBlockSeed {
+    type Value = Result<IRBlock, String>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut code = Vec::new();
+        let mut metadata = Vec::new();
+        let result = deserializer.deserialize_any(IRElementArrayVisitor::new("unable to deserialize IR block",
+            | index, mut instr_value: Value | {
+                code.push(IRInstruction::deserialize_value::<serde::de::value::Error>(&instr_value, self.strict_arguments, self.strict_opcode_codes)
+                    .map_err(| err | format!("instruction {}: {}", index, err))?);
+                match instr_value.get_mut("metadata").map(Value::take) {
+                    None | Some(serde_json::Value::Null) => (),
+                    Some(instr_metadata) => metadata.push((index, instr_metadata))
+                }
+                Ok(())
+            }))?;
+        Ok(result.map(| _ | {
+            let mut block = IRBlock::new(code);
+            for (index, instr_metadata) in metadata {
+                block.set_metadata(index, instr_metadata);
+            }
+            block
+        }))
     }
 }
 
@@ -201,39 +411,35 @@ impl<'de> Deserialize<'de> for IRBlock {
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        let instrs: Result<Vec<IRInstruction>, D::Error> = match value.as_array() {
-            Some(arr) => arr.iter()
-                .map(| instr_value | IRInstruction::deserialize(instr_value.clone().into_deserializer()).map_err(D::Error::custom))
-                .collect(),
-            None => Err(D::Error::custom("unable to deserialize IR block"))
-        };
-        Ok(IRBlock::new(instrs?))
+        IRBlockSeed { strict_arguments: false, strict_opcode_codes: false }.deserialize(deserializer)?
+            .map_err(D::Error::custom)
     }
 }
 
-impl<'de> IRType {
-    fn deserialize_typeentry_array<D, F>(deserializer: D, output_fn: &mut F) -> Result<(), D::Error>
+impl IRType {
+
+    fn deserialize_typeentry_array<E, F>(value: &serde_json::Value, depth: usize, output_fn: &mut F) -> Result<(), E>
     where
-        D: Deserializer<'de>,
-        F: FnMut(IRTypeEntry) -> ()
+        E: Error,
+        F: FnMut(IRTypeEntry)
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
         value.as_array()
-            .ok_or(D::Error::custom("unable to deserialize IR type"))?
+            .ok_or(E::custom("unable to deserialize IR type"))?
             .iter()
-            .try_for_each(| typeentry_value | IRType::deserialize_typeentry(typeentry_value, output_fn).map_err(D::Error::custom))?;
+            .try_for_each(| typeentry_value | IRType::deserialize_typeentry(typeentry_value, depth, output_fn))?;
         Ok(())
     }
 
-    fn deserialize_typeentry<D, F>(deserializer: D, output_fn: &mut F) -> Result<(), D::Error>
+    fn deserialize_typeentry<E, F>(typeentry_value: &serde_json::Value, depth: usize, output_fn: &mut F) -> Result<(), E>
     where
-        D: Deserializer<'de>,
-        F: FnMut(IRTypeEntry) -> ()
+        E: Error,
+        F: FnMut(IRTypeEntry)
     {
-        let typeentry_value = serde_json::Value::deserialize(deserializer)?;
-        let alignment = typeentry_value.get("alignment").map(| val | val.as_u64()).flatten();
-        match typeentry_value.get("type").map(| val | val.as_str()).flatten(){
+        if depth == 0 {
+            Err(E::custom("IR type entry nesting exceeds maximum depth"))?;
+        }
+        let alignment = typeentry_value.get("alignment").and_then(| val | val.as_u64());
+        match typeentry_value.get("type").and_then(| val | val.as_str()){
             Some("int8") => output_fn(IRTypeEntry::Int8{alignment}),
             Some("int16") => output_fn(IRTypeEntry::Int16{alignment}),
             Some("int32") => output_fn(IRTypeEntry::Int32{alignment}),
@@ -250,57 +456,58 @@ impl<'de> IRType {
             Some("bits") => output_fn(IRTypeEntry::Bits{
                 alignment,
                 width: typeentry_value.get("width")
-                    .map(| val | val.as_u64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR type entry"))?
+                    .and_then(| val | val.as_u64())
+                    .ok_or(E::custom("unable to deserialize IR type entry"))?
             }),
-            Some("builtin") => output_fn(match typeentry_value.get("class").map(| val | val.as_str()).flatten() {
+            Some("builtin") => output_fn(match typeentry_value.get("class").and_then(| val | val.as_str()) {
                 Some("vararg") => IRTypeEntry::Builtin{
                     alignment,
                     builtin: IRTypeBuiltin::VarargList
                 },
-                _ => Err(D::Error::custom("unable to deserialize IR type entry"))?
+                Some(class) => IRTypeEntry::Builtin{
+                    alignment,
+                    builtin: IRTypeBuiltin::Unknown(class.to_owned())
+                },
+                None => Err(E::custom("unable to deserialize IR type entry"))?
             }),
             Some("struct") => {
                 let fields = typeentry_value.get("fields")
-                    .ok_or(D::Error::custom("unable to deserialize IR type entry"))?;
+                    .ok_or(E::custom("unable to deserialize IR type entry"))?;
                 output_fn(IRTypeEntry::Struct{
                     alignment,
                     num_of_fields: fields
                         .as_array()
-                        .ok_or(D::Error::custom("unable to deserialize IR type entry"))?
+                        .ok_or(E::custom("unable to deserialize IR type entry"))?
                         .len()
                 });
-                IRType::deserialize_typeentry_array( fields.clone().into_deserializer(), output_fn).map_err(D::Error::custom)?;
+                IRType::deserialize_typeentry_array(fields, depth - 1, output_fn)?;
             },
             Some("union") => {
                 let fields = typeentry_value.get("fields")
-                    .ok_or(D::Error::custom("unable to deserialize IR type entry"))?;
+                    .ok_or(E::custom("unable to deserialize IR type entry"))?;
                 output_fn(IRTypeEntry::Union{
                     alignment,
                     num_of_fields: fields
                         .as_array()
-                        .ok_or(D::Error::custom("unable to deserialize IR type entry"))?
+                        .ok_or(E::custom("unable to deserialize IR type entry"))?
                         .len()
                 });
-                IRType::deserialize_typeentry_array(fields.clone().into_deserializer(), output_fn).map_err(D::Error::custom)?;
+                IRType::deserialize_typeentry_array(fields, depth - 1, output_fn)?;
             },
             Some("array") => {
                 output_fn(IRTypeEntry::Array{
                     alignment,
                     length: typeentry_value.get("length")
-                        .map(| val | val.as_u64())
-                        .flatten()
-                        .ok_or(D::Error::custom("unable to deserialize IR type entry"))?
+                        .and_then(| val | val.as_u64())
+                        .ok_or(E::custom("unable to deserialize IR type entry"))?
                 });
                 IRType::deserialize_typeentry(
                     typeentry_value.get("element_type")
-                        .ok_or(D::Error::custom("unable to deserialize IR type entry".to_owned()))?
-                        .clone()
-                        .into_deserializer(),
-                    output_fn).map_err(D::Error::custom)?;
+                        .ok_or(E::custom("unable to deserialize IR type entry".to_owned()))?,
+                    depth - 1,
+                    output_fn)?;
             },
-            _ => Err(D::Error::custom("unable to deserialize IR type entry"))?
+            _ => Err(E::custom("unable to deserialize IR type entry"))?
         };
         Ok(())
     }
@@ -312,22 +519,79 @@ impl<'de> Deserialize<'de> for IRType {
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        let identifier = value.get("identifier")
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR type"))?;
-        let mut type_content = Vec::new();
-        IRType::deserialize_typeentry_array(
-            value.get("type")
-                .ok_or(D::Error::custom("unable to deserialize IR type".to_owned()))?
-                .clone()
-                .into_deserializer(), 
-            &mut | x | type_content.push(x)).map_err(D::Error::custom)?;
+        IRTypeSeed { max_depth: DEFAULT_MAX_TYPE_DEPTH }.deserialize(deserializer)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IRTypeSeed {
+    max_depth: usize
+}
+
+impl<'de> DeserializeSeed<'de> for IRTypeSeed {
+    type Value = IRType;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for IRTypeSeed {
+    type Value = IRType;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an IR type object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut identifier = None;
+        let mut type_content = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "identifier" => identifier = Some(map.next_value::<Value>()?),
+                "type" => type_content = Some(map.next_value_seed(IRTypeEntriesSeed { max_depth: self.max_depth })?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let identifier = identifier.as_ref()
+            .and_then(| val | val.as_u64())
+            .ok_or(A::Error::custom("unable to deserialize IR type"))?;
+        let type_content = type_content.ok_or(A::Error::custom("unable to deserialize IR type"))?
+            .map_err(A::Error::custom)?;
         Ok(IRType::new(identifier, type_content))
     }
 }
 
+struct IRTypeEntriesSeed {
+    max_depth: usize
+}
+
+impl<'de> DeserializeSeed<'de> for IRTypeEntriesSeed {
+    type Value = Result<Vec<IRTypeEntry>, String>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut type_content = Vec::new();
+        let result = deserializer.deserialize_any(IRElementArrayVisitor::new("unable to deserialize IR type",
+            | _, value: Value | {
+                IRType::deserialize_typeentry::<serde::de::value::Error, _>(&value, self.max_depth, &mut | x | type_content.push(x))
+                    .map_err(| err | err.to_string())
+            }))?;
+        Ok(result.map(| _ | type_content))
+    }
+}
+
 impl<'de> Deserialize<'de> for IRFunctionDeclaration {
 
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -336,24 +600,22 @@ impl<'de> Deserialize<'de> for IRFunctionDeclaration {
     {
         let value = serde_json::Value::deserialize(deserializer)?;
         let identifier = value.get("identifier")
-            .map(| val | val.as_u64())
-            .flatten()
+            .and_then(| val | val.as_u64())
             .ok_or(D::Error::custom("unable to deserialize IR function declaration identifier"))?;
-        let name = value.get("name")
-            .map(| val | val.as_str())
-            .flatten()
-            .map(| x | x.to_owned());
+        let name = match value.get("name") {
+            Some(serde_json::Value::String(x)) => Some(x.to_owned()),
+            Some(serde_json::Value::Null) => None,
+            None => None,
+            _ => Err(D::Error::custom("unable to deserialize IR function declaration name"))?
+        };
         let params = value.get("parameters")
-            .map(| val | val.as_u64())
-            .flatten()
+            .and_then(| val | val.as_u64())
             .ok_or(D::Error::custom("unable to deserialize IR function declaration parameters"))?;
         let vararg = value.get("vararg")
-            .map(| val | val.as_bool())
-            .flatten()
+            .and_then(| val | val.as_bool())
             .ok_or(D::Error::custom("unable to deserialize IR function declaration vararg"))?;
         let returns = value.get("returns")
-            .map(| val | val.as_u64())
-            .flatten()
+            .and_then(| val | val.as_u64())
             .ok_or(D::Error::custom("unable to deserialize IR function declaration identifier"))?;
         Ok(IRFunctionDeclaration::new(identifier, name, params, vararg, returns))
     }
@@ -365,135 +627,302 @@ impl<'de> Deserialize<'de> for IRFunction {
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        let identifier = value.get("identifier")
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR function identifier"))?;
-        let name = value.get("name")
-            .map(| val | val.as_str())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR function name"))?.to_owned();
-        let locals = value.get("locals")
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR function locals"))?;
-        let body = IRBlock::deserialize(
-            value.get("body")
-                .ok_or(D::Error::custom("unable to deserialize IR function body"))?
-                .clone()
-                .into_deserializer())
-            .map_err(D::Error::custom)?;
-        Ok(IRFunction::new(name, identifier, locals, body))
+        IRFunctionSeed { strict_arguments: false, strict_opcode_codes: false }.deserialize(deserializer)
     }
 }
 
-impl<'de> Deserialize<'de> for IRDataElement {
+#[derive(Clone, Copy)]
+struct IRFunctionSeed {
+    strict_arguments: bool,
+    strict_opcode_codes: bool
+}
 
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+impl<'de> DeserializeSeed<'de> for IRFunctionSeed {
+    type Value = IRFunction;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        let data_entry = match value.get("class").map(| val | val.as_str()).flatten() {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for IRFunctionSeed {
+    type Value = IRFunction;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an IR function object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut identifier = None;
+        let mut name = None;
+        let mut locals = None;
+        let mut body = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "identifier" => identifier = Some(map.next_value::<Value>()?),
+                "name" => name = Some(map.next_value::<Value>()?),
+                "locals" => locals = Some(map.next_value::<Value>()?),
+                "body" => body = Some(map.next_value_seed(IRBlockSeed {
+                    strict_arguments: self.strict_arguments,
+                    strict_opcode_codes: self.strict_opcode_codes
+                })?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let identifier = identifier.as_ref()
+            .and_then(| val | val.as_u64())
+            .ok_or(A::Error::custom("unable to deserialize IR function identifier"))?;
+        let name = name.as_ref()
+            .and_then(| val | val.as_str())
+            .ok_or(A::Error::custom("unable to deserialize IR function name"))?.to_owned();
+        let locals = locals.as_ref()
+            .and_then(| val | val.as_u64())
+            .ok_or_else(|| A::Error::custom(format!("in function `{}`: unable to deserialize IR function locals", name)))?;
+        let body = body
+            .ok_or_else(|| A::Error::custom(format!("in function `{}`: unable to deserialize IR function body", name)))?
+            .map_err(| err | A::Error::custom(format!("in function `{}`, {}", name, err)))?;
+        Ok(IRFunction::new(name, identifier, locals, body))
+    }
+}
+
+impl IRDataElement {
+    fn deserialize_value<E>(value: &serde_json::Value) -> Result<Self, E>
+    where
+        E: Error,
+    {
+        let data_entry = match value.get("class").and_then(| val | val.as_str()) {
             Some("undefined") => IRDataElement::Undefined(
                 value.get("count")
-                    .map(| val | val.as_u64())
-                    .flatten()
+                    .and_then(| val | val.as_u64())
                     .unwrap_or(1)),
             Some("aggregate") => IRDataElement::Aggregate,
             Some("integer") => IRDataElement::Integer(
                 value.get("value")
-                    .map(| val | val.as_i64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR integral data value"))?),
+                    .and_then(| val | val.as_i64())
+                    .ok_or(E::custom("unable to deserialize IR integral data value"))?),
             Some("float32") => IRDataElement::Float32(
                 value.get("value")
-                    .map(| val | val.as_f64())
-                    .flatten()
+                    .and_then(| val | val.as_f64())
                     .map(| x | x as f32)
-                    .ok_or(D::Error::custom("unable to deserialize IR floating-point data value"))?),
+                    .ok_or(E::custom("unable to deserialize IR floating-point data value"))?),
             Some("float64") => IRDataElement::Float64(
                 value.get("value")
-                    .map(| val | val.as_f64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR floating-point data value"))?),
+                    .and_then(| val | val.as_f64())
+                    .ok_or(E::custom("unable to deserialize IR floating-point data value"))?),
             Some("long_double") => IRDataElement::LongDouble(
-                value.get("value")
-                    .map(| val | val.as_f64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR floating-point data value"))?),
+                match value.get("value") {
+                    Some(serde_json::Value::Number(x)) => IRLongDouble::new(x.to_string()),
+                    _ => None
+                }.ok_or(E::custom("unable to deserialize IR floating-point data value"))?),
             Some("string") => IRDataElement::String(
                 value.get("content")
-                    .map(| val | val.as_str())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR string data value"))?.to_owned().into_bytes()),
+                    .and_then(| val | val.as_str())
+                    .ok_or(E::custom("unable to deserialize IR string data value"))?.to_owned().into_bytes()),
             Some("pointer") => IRDataElement::Pointer{
                 base: value.get("reference")
-                    .map(| val | val.as_str())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR pointer data value"))?.to_owned(),
+                    .and_then(| val | val.as_str())
+                    .ok_or(E::custom("unable to deserialize IR pointer data value"))?.to_owned(),
                 offset: value.get("offset")
-                    .map(| val | val.as_i64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR pointer data value"))?
+                    .and_then(| val | val.as_i64())
+                    .ok_or(E::custom("unable to deserialize IR pointer data value"))?
             },
             Some("string_pointer") => IRDataElement::StringPointer{
                 base: value.get("string")
-                    .map(| val | val.as_u64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR pointer data value"))?,
+                    .and_then(| val | val.as_u64())
+                    .ok_or(E::custom("unable to deserialize IR pointer data value"))?,
                 offset: value.get("offset")
-                    .map(| val | val.as_i64())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR pointer data value"))?
+                    .and_then(| val | val.as_i64())
+                    .ok_or(E::custom("unable to deserialize IR pointer data value"))?
             },
             Some("raw") => {
-                match value.get("value").map(| val | val.as_array()).flatten() {
+                match value.get("value").and_then(| val | val.as_array()) {
                     Some(arr) => IRDataElement::Raw(arr
                         .iter()
-                        .map(| x | x
-                            .as_u64()
-                            .map(| e | e as u8)
-                            .ok_or(D::Error::custom("unable to deserialize IR raw data")))
-                        .collect::<Result<Vec<u8>, D::Error>>()?),
-                    None => Err(D::Error::custom("unable to deserialize IR raw data"))?
+                        .map(| x | {
+                            let byte = x
+                                .as_u64()
+                                .ok_or(E::custom("unable to deserialize IR raw data"))?;
+                            u8::try_from(byte)
+                                .map_err(| _ | E::custom(format!("IR raw data byte {} is out of u8 range", byte)))
+                        })
+                        .collect::<Result<Vec<u8>, E>>()?),
+                    None => Err(E::custom("unable to deserialize IR raw data"))?
                 }
             },
-            _ => Err(D::Error::custom("unable to deserialize IR data element"))?
+            Some(class) => IRDataElement::Unknown{
+                class: class.to_owned(),
+                value: value.clone()
+            },
+            None => Err(E::custom("unable to deserialize IR data element"))?
         };
         Ok(data_entry)
     }
 }
 
-impl<'de> Deserialize<'de> for IRData {
+#[derive(Deserialize)]
+struct IRRawDataValue<'a> {
+    #[serde(borrow)]
+    value: &'a RawValue
+}
 
+impl IRDataElement {
+    fn deserialize_raw<E>(raw: &RawValue) -> Result<Self, E>
+    where
+        E: Error,
+    {
+        let value = serde_json::from_str::<Value>(raw.get())
+            .map_err(E::custom)?;
+        match (value.get("class").and_then(| val | val.as_str()), value.get("value")) {
+            (Some("long_double"), Some(serde_json::Value::Number(_))) => {
+                let raw_value = serde_json::from_str::<IRRawDataValue>(raw.get())
+                    .map_err(E::custom)?;
+                IRLongDouble::new(raw_value.value.get())
+                    .map(IRDataElement::LongDouble)
+                    .ok_or(E::custom("unable to deserialize IR floating-point data value"))
+            },
+            _ => IRDataElement::deserialize_value(&value)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IRDataElement {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         let value = serde_json::Value::deserialize(deserializer)?;
-        let identifier = value.get("identifier")
-            .map(| val | val.as_str())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR data identifier"))?.to_owned();
-        let storage = match value.get("storage").map(| val | val.as_str()).flatten() {
+        IRDataElement::deserialize_value(&value)
+    }
+}
+
+impl<'de> Deserialize<'de> for IRData {
+
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        IRDataSeed { raw_numbers: false }.deserialize(deserializer)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IRDataSeed {
+    raw_numbers: bool
+}
+
+impl<'de> DeserializeSeed<'de> for IRDataSeed {
+    type Value = IRData;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for IRDataSeed {
+    type Value = IRData;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an IR data object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut identifier = None;
+        let mut storage = None;
+        let mut type_id = None;
+        let mut data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "identifier" => identifier = Some(map.next_value::<Value>()?),
+                "storage" => storage = Some(map.next_value::<Value>()?),
+                "type" => type_id = Some(map.next_value::<Value>()?),
+                "value" => data = Some(map.next_value_seed(IRDataElementsSeed { raw_numbers: self.raw_numbers })?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let identifier = identifier.as_ref()
+            .and_then(| val | val.as_str())
+            .ok_or(A::Error::custom("unable to deserialize IR data identifier"))?.to_owned();
+        let storage = match storage.as_ref().and_then(| val | val.as_str()) {
             Some("global") => IRDataStorage::Global,
             Some("thread_local") => IRDataStorage::ThreadLocal,
-            _ => Err(D::Error::custom("unable to deserialize IR data storage specifier"))?
+            _ => Err(A::Error::custom("unable to deserialize IR data storage specifier"))?
         };
-        let type_id = value.get("type")
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR data type"))?;
-        let data: Result<Vec<IRDataElement>, _> = match value.get("value").map(| val | val.as_array()).flatten() {
-            Some(x) => x.iter().map(| x | IRDataElement::deserialize(x.clone().into_deserializer()).map_err(D::Error::custom)).collect(),
-            None => Err(D::Error::custom("unable to deserialize IR data"))
+        let type_id = type_id.as_ref()
+            .and_then(| val | val.as_u64())
+            .ok_or(A::Error::custom("unable to deserialize IR data type"))?;
+        let data = data.ok_or(A::Error::custom("unable to deserialize IR data"))?
+            .map_err(A::Error::custom)?;
+        Ok(IRData::new(identifier, storage, type_id, data))
+    }
+}
+
+struct IRDataElementsSeed {
+    raw_numbers: bool
+}
+
+impl<'de> DeserializeSeed<'de> for IRDataElementsSeed {
+    type Value = Result<Vec<IRDataElement>, String>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut elements = Vec::new();
+        let result = if self.raw_numbers {
+            deserializer.deserialize_any(IRElementArrayVisitor::new("unable to deserialize IR data",
+                | _, raw: Box<RawValue> | {
+                    elements.push(IRDataElement::deserialize_raw::<serde::de::value::Error>(&raw)
+                        .map_err(| err | err.to_string())?);
+                    Ok(())
+                }))?
+        } else {
+            deserializer.deserialize_any(IRElementArrayVisitor::new("unable to deserialize IR data",
+                | _, value: Value | {
+                    elements.push(IRDataElement::deserialize_value::<serde::de::value::Error>(&value)
+                        .map_err(| err | err.to_string())?);
+                    Ok(())
+                }))?
         };
-        Ok(IRData::new(identifier, storage, type_id, data?))
+        Ok(result.map(| _ | elements))
     }
 }
 
+fn deserialize_string_literal_units<E, T>(value: &Value, type_name: &str) -> Result<Vec<T>, E>
+where
+    E: Error,
+    T: TryFrom<u64> {
+    value.get("literal")
+        .and_then(| val | val.as_array())
+        .ok_or(E::custom("unable to deserialize IR string literal content"))?
+        .iter()
+        .map(| elem | {
+            let unit = elem
+                .as_u64()
+                .ok_or(E::custom("unable to deserialize IR string literal content"))?;
+            T::try_from(unit)
+                .map_err(| _ | E::custom(format!("IR string literal code unit {} is out of {} range", unit, type_name)))
+        })
+        .collect()
+}
+
 impl<'de> Deserialize<'de> for IRStringLiteral {
 
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -502,44 +931,21 @@ impl<'de> Deserialize<'de> for IRStringLiteral {
     {
         let value = serde_json::Value::deserialize(deserializer)?;
         let identifier = value.get("id")
-            .map(| val | val.as_u64())
-            .flatten()
+            .and_then(| val | val.as_u64())
             .ok_or(D::Error::custom("unable to deserialize IR string literal"))?;
         let public = value.get("public")
-            .map(| val | val.as_bool())
-            .flatten()
+            .and_then(| val | val.as_bool())
             .ok_or(D::Error::custom("unable to deserialize IR string literal"))?;
-        let content = match value.get("type").map(| val | val.as_str()).flatten() {
+        let content = match value.get("type").and_then(| val | val.as_str()) {
             Some("multibyte") =>
                 IRStringLiteralContent::Multibyte(value.get("literal")
-                    .map(| val | val.as_str())
-                    .flatten()
+                    .and_then(| val | val.as_str())
                     .map(| x | x.to_owned().into_bytes())
                     .ok_or(D::Error::custom("unable to deserialize IR string literal content"))?),
             Some("unicode16") =>
-                IRStringLiteralContent::Unicode16(value.get("literal")
-                    .map(| val | val.as_array())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR string literal content"))?
-                    .iter()
-                    .map(| elem: &serde_json::Value |
-                        elem
-                            .as_u64()
-                            .map(| x | x as u16)
-                            .ok_or(D::Error::custom("unable to deserialize IR string literal content")))
-                    .collect::<Result<Vec<u16>, D::Error>>()?),
+                IRStringLiteralContent::Unicode16(deserialize_string_literal_units::<D::Error, u16>(&value, "u16")?),
             Some("unicode32") =>
-                IRStringLiteralContent::Unicode32(value.get("literal")
-                    .map(| val | val.as_array())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR string literal content"))?
-                    .iter()
-                    .map(| elem: &serde_json::Value |
-                        elem
-                            .as_u64()
-                            .map(| x | x as u32)
-                            .ok_or(D::Error::custom("unable to deserialize IR string literal content")))
-                    .collect::<Result<Vec<u32>, D::Error>>()?),
+                IRStringLiteralContent::Unicode32(deserialize_string_literal_units::<D::Error, u32>(&value, "u32")?),
             _ => Err(D::Error::custom("unable to deserialize IR string literal content"))?
         };
 
@@ -549,20 +955,21 @@ impl<'de> Deserialize<'de> for IRStringLiteral {
 
 impl<'de> IRInlineAssembly {
 
-    fn deserialize_param_class<D>(param_value: &serde_json::Value, type_field: &str, type_index_field: &str, index_field: &str) -> Result<(IRTypeRef, u64), D::Error>
+    fn param_field_error<E: Error>(param_id: IRIdentifier, field: &str) -> E {
+        E::custom(format!("unable to deserialize IR inline assembly parameter {} field `{}`", param_id, field))
+    }
+
+    fn deserialize_param_class<D>(param_value: &serde_json::Value, param_id: IRIdentifier, type_field: &str, type_index_field: &str, index_field: &str) -> Result<(IRTypeRef, u64), D::Error>
         where D: Deserializer<'de> {
         let type_id = param_value.get(type_field)
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))?;
+            .and_then(| val | val.as_u64())
+            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, type_field))?;
         let type_index = param_value.get(type_index_field)
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))? as usize;
+            .and_then(| val | val.as_u64())
+            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, type_index_field))? as usize;
         let index = param_value.get(index_field)
-            .map(| val | val.as_u64())
-            .flatten()
-            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))?;
+            .and_then(| val | val.as_u64())
+            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, index_field))?;
         Ok((IRTypeRef::new(type_id, type_index), index))
     }
 }
@@ -575,117 +982,108 @@ impl<'de> Deserialize<'de> for IRInlineAssembly {
     {
         let value = serde_json::Value::deserialize(deserializer)?;
         let identifier = value.get("identifier")
-            .map(| val | val.as_u64())
-            .flatten()
+            .and_then(| val | val.as_u64())
             .ok_or(D::Error::custom("unable to deserialize IR inline assembly identifier"))?;
         let global = value.get("global")
-            .map(| val | val.as_bool())
-            .flatten()
+            .and_then(| val | val.as_bool())
             .ok_or(D::Error::custom("unable to deserialize IR inline assembly properties"))?;
         let template = value.get("template")
-            .map(| val | val.as_str())
-            .flatten()
+            .and_then(| val | val.as_str())
             .ok_or(D::Error::custom("unable to deserialize IR inline assembly template"))?.to_owned();
         let parameters = value.get("parameters")
-            .map(| val | val.as_array())
-            .flatten()
+            .and_then(| val | val.as_array())
             .ok_or(D::Error::custom("unable to deserialize IR inline assembly parameters"))?
             .iter()
             .map(| param_value | -> Result<(IRIdentifier, IRInlineAssemblyParameter), D::Error> {
                 let param_id = param_value.get("identifier")
-                    .map(| val | val.as_u64())
-                    .flatten()
+                    .and_then(| val | val.as_u64())
                     .ok_or(D::Error::custom("unable to deserialize IR inline assembly parameter identifier"))?;
                 let param_aliases = param_value.get("names")
-                    .map(| val | val.as_array())
-                    .flatten()
-                    .ok_or(D::Error::custom("unable to deserialize IR inline assembly parameter aliases"))?
+                    .and_then(| val | val.as_array())
+                    .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, "names"))?
                     .iter()
                     .map(| alias_value | -> Result<String, D::Error>{
                         alias_value
                             .as_str()
                             .map(| s | s.to_owned())
-                            .ok_or(D::Error::custom("unable to deserialize IR inline assembly parameter aliases"))
+                            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, "names"))
                     })
                     .collect::<Result<Vec<String>, D::Error>>()?;
-                let param_class = match param_value.get("class").map(| val | val.as_str()).flatten() {
+                let param_class = match param_value.get("class").and_then(| val | val.as_str()) {
                     Some("read") => {
-                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "type", "type_index", "from")?;
+                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "type", "type_index", "from")?;
                         IRInlineAssemblyParameterClass::Read(typeref, index)
                     },
 
                     Some("load") => {
-                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "type", "type_index", "from")?;
+                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "type", "type_index", "from")?;
                         IRInlineAssemblyParameterClass::Load(typeref, index)
                     },
 
                     Some("store") => {
-                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "type", "type_index", "to")?;
+                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "type", "type_index", "to")?;
                         IRInlineAssemblyParameterClass::Store(typeref, index)
                     },
 
                     Some("load_store") => {
-                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "type", "type_index", "from_to")?;
+                        let (typeref, index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "type", "type_index", "from_to")?;
                         IRInlineAssemblyParameterClass::LoadStore(typeref, index)
                     },
 
                     Some("read_store") => {
-                        let (from_typeref, from_index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "from_type", "from_type_index", "from")?;
-                        let (to_typeref, to_index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, "from_type", "from_type_index", "from")?;
+                        let (from_typeref, from_index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "from_type", "from_type_index", "from")?;
+                        let (to_typeref, to_index) = IRInlineAssembly::deserialize_param_class::<D>(param_value, param_id, "type", "type_index", "to")?;
                         IRInlineAssemblyParameterClass::ReadStore(from_typeref, from_index, to_typeref, to_index)
                     },
 
                     Some("immediate") => {
                         let type_id = param_value.get("type")
-                            .map(| val | val.as_u64())
-                            .flatten()
-                            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))?;
+                            .and_then(| val | val.as_u64())
+                            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, "type"))?;
                         let type_index = param_value.get("type_index")
-                            .map(| val | val.as_u64())
-                            .flatten()
-                            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))? as usize;
+                            .and_then(| val | val.as_u64())
+                            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, "type_index"))? as usize;
                         let type_ref = IRTypeRef::new(type_id, type_index);
                         let imm_value = param_value.get("value")
-                            .map(| val | val.as_i64())
-                            .flatten()
-                            .ok_or(D::Error::custom("unable to deserialize IR inline assembly class"))?;
+                            .and_then(| val | val.as_i64())
+                            .ok_or_else(|| IRInlineAssembly::param_field_error::<D::Error>(param_id, "value"))?;
 
-                        match param_value.get("variant").map(| val | val.as_str()).flatten() {
+                        match param_value.get("variant").and_then(| val | val.as_str()) {
                             Some("identifier_based") => {
-                                match param_value.as_str() {
-                                    Some(x) => IRInlineAssemblyParameterClass::ImmediateIdentifierBased(type_ref, x.to_owned(), imm_value),
-                                    _ => IRInlineAssemblyParameterClass::ImmediateConstant(type_ref, imm_value)
+                                match param_value.get("base") {
+                                    None | Some(serde_json::Value::Null) => IRInlineAssemblyParameterClass::ImmediateConstant(type_ref, imm_value),
+                                    Some(serde_json::Value::String(x)) => IRInlineAssemblyParameterClass::ImmediateIdentifierBased(type_ref, x.to_owned(), imm_value),
+                                    _ => Err(IRInlineAssembly::param_field_error::<D::Error>(param_id, "base"))?
                                 }
                             },
 
                             Some("literal_based") => {
-                                match param_value.as_u64() {
+                                match param_value.get("base").and_then(| val | val.as_u64()) {
                                     Some(x) => IRInlineAssemblyParameterClass::ImmediateLiteralBased(type_ref, x, imm_value),
-                                    _ => Err(D::Error::custom("unable to deserialize IR inline assembly class"))?
+                                    _ => Err(IRInlineAssembly::param_field_error::<D::Error>(param_id, "base"))?
                                 }
                             }
 
-                            _ => Err(D::Error::custom("unable to deserialize IR inline assembly class"))?
+                            _ => Err(IRInlineAssembly::param_field_error::<D::Error>(param_id, "variant"))?
                         }
                     }
 
-                    _ => Err(D::Error::custom("unable to deserialize IR inline assembly class"))?
+                    _ => Err(IRInlineAssembly::param_field_error::<D::Error>(param_id, "class"))?
                 };
 
-                let param_constraint = match param_value.get("constraint").map(| val | val.as_str()).flatten() {
+                let param_constraint = match param_value.get("constraint").and_then(| val | val.as_str()) {
                     Some("none") => IRInlineAssemblyParameterConstraint::None,
                     Some("register") => IRInlineAssemblyParameterConstraint::Register,
                     Some("memory") => IRInlineAssemblyParameterConstraint::Memory,
                     Some("register_memory") => IRInlineAssemblyParameterConstraint::RegisterMemory,
-                    _ => Err(D::Error::custom("unable to deserialize IR inline assembly class"))?
+                    _ => Err(IRInlineAssembly::param_field_error::<D::Error>(param_id, "constraint"))?
                 };
 
                 Ok((param_id, IRInlineAssemblyParameter::new(param_id, param_aliases, param_class, param_constraint)))
             })
             .collect::<Result<HashMap<_, _>, _>>()?;
         let clobbers = value.get("clobbers")
-            .map(| val | val.as_array())
-            .flatten()
+            .and_then(| val | val.as_array())
             .ok_or(D::Error::custom("unable to deserialize IR inline assembly clobbers"))?
             .iter()
             .map(| elem | elem
@@ -694,18 +1092,15 @@ impl<'de> Deserialize<'de> for IRInlineAssembly {
                 .ok_or(D::Error::custom("unable to deserialize IR inline assembly clobbers")))
             .collect::<Result<HashSet<String>, D::Error>>()?;
         let jump_targets = value.get("jump_targets")
-                .map(| val | val.as_array())
-                .flatten()
+                .and_then(| val | val.as_array())
                 .ok_or(D::Error::custom("unable to deserialize IR inline assembly jump targets"))?
                 .iter()
                 .map(| jump_elem | -> Result<(IRIdentifier, IRInlineAssemblyJumpTarget), D::Error> {
                     let jump_identifier = jump_elem.get("identifier")
-                        .map(| val | val.as_u64())
-                        .flatten()
+                        .and_then(| val | val.as_u64())
                         .ok_or(D::Error::custom("unable to deserialize IR inline assembly jump target"))?;
                     let jump_aliases = jump_elem.get("names")
-                        .map(| val | val.as_array())
-                        .flatten()
+                        .and_then(| val | val.as_array())
                         .ok_or(D::Error::custom("unable to deserialize IR inline assembly jump target"))?
                         .iter()
                         .map(| alias_value | -> Result<String, D::Error> {
@@ -716,13 +1111,11 @@ impl<'de> Deserialize<'de> for IRInlineAssembly {
                         })
                         .collect::<Result<Vec<String>, D::Error>>()?;
                     let jump_function = jump_elem.get("function")
-                        .map(| val | val.as_str())
-                        .flatten()
+                        .and_then(| val | val.as_str())
                         .ok_or(D::Error::custom("unable to deserialize IR inline assembly jump target"))?
                         .to_owned();
                     let jump_target = jump_elem.get("target")
-                        .map(| val | val.as_u64())
-                        .flatten()
+                        .and_then(| val | val.as_u64())
                         .ok_or(D::Error::custom("unable to deserialize IR inline assembly jump target"))? as usize;
                     Ok((jump_identifier, IRInlineAssemblyJumpTarget::new(jump_identifier, jump_aliases, jump_function, jump_target)))
                 })
@@ -733,74 +1126,911 @@ impl<'de> Deserialize<'de> for IRInlineAssembly {
     }
 }
 
-impl<'de> IRModule {
-    fn deserialize_array<D, E>(value: &serde_json::Value, err: E) -> Result<Vec<D>, E>
+struct IRModuleSectionSeed<S> {
+    section: &'static str,
+    element: S
+}
+
+impl<S> IRModuleSectionSeed<S> {
+    fn new(section: &'static str, element: S) -> IRModuleSectionSeed<S> {
+        IRModuleSectionSeed {
+            section,
+            element
+        }
+    }
+}
+
+impl<'de, S> DeserializeSeed<'de> for IRModuleSectionSeed<S>
+where S: DeserializeSeed<'de> + Clone {
+    type Value = Vec<S::Value>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, S> Visitor<'de> for IRModuleSectionSeed<S>
+where S: DeserializeSeed<'de> + Clone {
+    type Value = Vec<S::Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an array of IR module {}", self.section)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
     where
-        D: Deserialize<'de>,
-        E: Error {
-        match value.as_array() {
-            Some(arr) => arr.iter().map(| x | D::deserialize(x.clone().into_deserializer()).map_err(E::custom)).collect(),
-            None => Err(err)
+        A: SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(self.element.clone())? {
+            elements.push(element);
         }
+        Ok(elements)
     }
 }
 
+struct IRModuleVisitor {
+    max_type_depth: usize,
+    strict_arguments: bool,
+    strict_opcode_codes: bool,
+    require_sections: bool,
+    raw_numbers: bool
+}
+
+impl<'de> Visitor<'de> for IRModuleVisitor {
+    type Value = IRModule;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an IR module object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut globals = None;
+        let mut externals = None;
+        let mut types = None;
+        let mut decls = None;
+        let mut funcs = None;
+        let mut data = None;
+        let mut string_literals = None;
+        let mut inline_assembly = None;
+        let mut opcode_revision = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "globals" => globals = Some(map.next_value_seed(IRModuleSectionSeed::new("globals", PhantomData::<IRSymbol>))?),
+                "externals" => externals = Some(map.next_value_seed(IRModuleSectionSeed::new("externals", PhantomData::<IRSymbol>))?),
+                "types" => types = Some(map.next_value_seed(IRModuleSectionSeed::new("types", IRTypeSeed { max_depth: self.max_type_depth }))?),
+                "function_declarations" => decls = Some(map.next_value_seed(IRModuleSectionSeed::new("function declarations", PhantomData::<IRFunctionDeclaration>))?),
+                "functions" => funcs = Some(map.next_value_seed(IRModuleSectionSeed::new("functions", IRFunctionSeed {
+                    strict_arguments: self.strict_arguments,
+                    strict_opcode_codes: self.strict_opcode_codes
+                }))?),
+                "data" => data = Some(map.next_value_seed(IRModuleSectionSeed::new("data", IRDataSeed { raw_numbers: self.raw_numbers }))?),
+                "string_literals" => string_literals = Some(map.next_value_seed(IRModuleSectionSeed::new("string literals", PhantomData::<IRStringLiteral>))?),
+                "inline_assembly" => inline_assembly = Some(map.next_value_seed(IRModuleSectionSeed::new("inline assembly", PhantomData::<IRInlineAssembly>))?),
+                "opcode_revision" => {
+                    opcode_revision = map.next_value::<Option<u64>>()?;
+                    match (opcode_revision, IRInstruction::revision()) {
+                        (Some(revision), Some(expected)) if revision != expected =>
+                            Err(A::Error::custom(format!("IR module opcode revision {} does not match supported revision {}", revision, expected)))?,
+                        _ => ()
+                    };
+                },
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        if self.require_sections {
+            let sections = [
+                ("globals", globals.is_some()),
+                ("externals", externals.is_some()),
+                ("types", types.is_some()),
+                ("function_declarations", decls.is_some()),
+                ("functions", funcs.is_some()),
+                ("data", data.is_some()),
+                ("string_literals", string_literals.is_some()),
+                ("inline_assembly", inline_assembly.is_some())
+            ];
+            if let Some((section, _)) = sections.iter().find(| (_, present) | !present) {
+                Err(A::Error::missing_field(section))?
+            }
+        }
+
+        let mut builder = IRModuleBuilder::new();
+        for sym in globals.unwrap_or_default() {
+            builder.add_global(sym).map_err(A::Error::custom)?;
+        }
+        for sym in externals.unwrap_or_default() {
+            builder.add_external(sym).map_err(A::Error::custom)?;
+        }
+        for tp in types.unwrap_or_default() {
+            builder.add_type(tp).map_err(A::Error::custom)?;
+        }
+        for decl in decls.unwrap_or_default() {
+            builder.add_function_declaration(decl).map_err(A::Error::custom)?;
+        }
+        for func in funcs.unwrap_or_default() {
+            builder.add_function(func).map_err(A::Error::custom)?;
+        }
+        for data in data.unwrap_or_default() {
+            builder.add_data(data).map_err(A::Error::custom)?;
+        }
+        for literal in string_literals.unwrap_or_default() {
+            builder.add_string_literal(literal).map_err(A::Error::custom)?;
+        }
+        for inline_asm in inline_assembly.unwrap_or_default() {
+            builder.add_inline_assembly(inline_asm).map_err(A::Error::custom)?;
+        }
+        let mut module = builder.build()
+            .map_err(A::Error::custom)?;
+        module.set_opcode_revision(opcode_revision);
+        Ok(module)
+    }
+}
+
+struct IRFunctionStreamVisitor<'a, F, E> {
+    strict_arguments: bool,
+    strict_opcode_codes: bool,
+    context: &'a IRModule,
+    callback: &'a mut F,
+    callback_error: &'a mut Option<E>
+}
+
+impl<'de, 'a, F, E> DeserializeSeed<'de> for IRFunctionStreamVisitor<'a, F, E>
+where F: FnMut(&IRModule, IRFunction) -> Result<(), E> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 'a, F, E> Visitor<'de> for IRFunctionStreamVisitor<'a, F, E>
+where F: FnMut(&IRModule, IRFunction) -> Result<(), E> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an array of IR module functions")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(func) = seq.next_element_seed(IRFunctionSeed {
+            strict_arguments: self.strict_arguments,
+            strict_opcode_codes: self.strict_opcode_codes
+        })? {
+            self.context.check_function_typing(&func)
+                .map_err(A::Error::custom)?;
+            if let Err(err) = (self.callback)(self.context, func) {
+                *self.callback_error = Some(err);
+                Err(A::Error::custom("IR function callback failed"))?
+            }
+        }
+        Ok(())
+    }
+}
+
+const UTF8_BYTE_ORDER_MARK: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+fn skip_byte_order_mark<R: io::Read>(mut reader: R) -> io::Result<io::Chain<io::Cursor<Vec<u8>>, R>> {
+    let mut prefix = Vec::with_capacity(UTF8_BYTE_ORDER_MARK.len());
+    (&mut reader).take(UTF8_BYTE_ORDER_MARK.len() as u64).read_to_end(&mut prefix)?;
+    if prefix == UTF8_BYTE_ORDER_MARK {
+        prefix.clear();
+    }
+    Ok(io::Cursor::new(prefix).chain(reader))
+}
+
+struct IRModuleStreamVisitor<'a, F, E> {
+    max_type_depth: usize,
+    strict_arguments: bool,
+    strict_opcode_codes: bool,
+    callback: &'a mut F,
+    callback_error: &'a mut Option<E>
+}
+
+impl<'a, F, E> IRModuleStreamVisitor<'a, F, E> {
+    fn context(types: Vec<IRType>, decls: Vec<IRFunctionDeclaration>) -> Result<IRModule, IRError> {
+        let mut builder = IRModuleBuilder::new();
+        for tp in types {
+            builder.add_type(tp)?;
+        }
+        for decl in decls {
+            builder.add_function_declaration(decl)?;
+        }
+        builder.build()
+    }
+
+    fn functions<'b>(&'b mut self, context: &'b IRModule) -> IRFunctionStreamVisitor<'b, F, E> {
+        IRFunctionStreamVisitor {
+            strict_arguments: self.strict_arguments,
+            strict_opcode_codes: self.strict_opcode_codes,
+            context,
+            callback: &mut *self.callback,
+            callback_error: &mut *self.callback_error
+        }
+    }
+}
+
+impl<'de, 'a, F, E> Visitor<'de> for IRModuleStreamVisitor<'a, F, E>
+where F: FnMut(&IRModule, IRFunction) -> Result<(), E> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an IR module object")
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut types = None;
+        let mut decls = None;
+        let mut deferred_functions = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "types" => types = Some(map.next_value_seed(IRModuleSectionSeed::new("types", IRTypeSeed { max_depth: self.max_type_depth }))?),
+                "function_declarations" => decls = Some(map.next_value_seed(IRModuleSectionSeed::new("function declarations", PhantomData::<IRFunctionDeclaration>))?),
+                "functions" if types.is_some() && decls.is_some() => {
+                    let context = IRModuleStreamVisitor::<F, E>::context(types.take().unwrap_or_default(), decls.take().unwrap_or_default())
+                        .map_err(A::Error::custom)?;
+                    map.next_value_seed(self.functions(&context))?
+                },
+                "functions" => deferred_functions = Some(map.next_value::<Box<RawValue>>()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        if let Some(functions) = deferred_functions {
+            let context = IRModuleStreamVisitor::<F, E>::context(types.unwrap_or_default(), decls.unwrap_or_default())
+                .map_err(A::Error::custom)?;
+            self.functions(&context).deserialize(&mut serde_json::Deserializer::from_str(functions.get()))
+                .map_err(A::Error::custom)?;
+        }
+        Ok(())
+    }
+}
 
 impl<'de> Deserialize<'de> for IRModule {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        let globals = IRModule::deserialize_array::<IRSymbol, D::Error>(
-            value.get("globals").ok_or(D::Error::custom("unable to deserialize IR module globals"))?,
-            D::Error::custom("unable to deserialize IR module globals"))?
-            .into_iter()
-            .map(| sym | (sym.name().to_owned(), sym))
-            .collect::<HashMap<String, _>>();
-        let externals = IRModule::deserialize_array::<IRSymbol, D::Error>(
-            value.get("externals").ok_or( D::Error::custom("unable to deserialize IR module externals"))?,
-            D::Error::custom("unable to deserialize IR module externals"))?
-            .into_iter()
-            .map(| sym | (sym.name().to_owned(), sym))
-            .collect::<HashMap<String, _>>();
-        let types = IRModule::deserialize_array::<IRType, D::Error>(
-            value.get("types").ok_or(D::Error::custom("unable to deserialize IR module types"))?,
-            D::Error::custom("unable to deserialize IR module types"))?
-            .into_iter()
-            .map(| tp | (tp.identifier(), tp))
-            .collect::<HashMap<IRIdentifier, _>>();
-        let decls = IRModule::deserialize_array::<IRFunctionDeclaration, D::Error>(
-            value.get("function_declarations").ok_or(D::Error::custom("unable to deserialize IR module function declarations"))?,
-            D::Error::custom("unable to deserialize IR module function declarations"))?
-            .into_iter()
-            .map(| tp | (tp.identifier(), tp))
-            .collect::<HashMap<IRIdentifier, _>>();
-        let funcs = IRModule::deserialize_array::<IRFunction, D::Error>(
-            value.get("functions").ok_or(D::Error::custom("unable to deserialize IR module functions"))?,
-            D::Error::custom("unable to deserialize IR module functions"))?
-            .into_iter()
-            .map(| sym | (sym.name().to_owned(), sym))
-            .collect::<HashMap<String, _>>();
-        let data = IRModule::deserialize_array::<IRData, D::Error>(
-        value.get("data").ok_or(D::Error::custom("unable to deserialize IR module data"))?,
-         D::Error::custom("unable to deserialize IR module data"))?
-            .into_iter()
-            .map(| tp | (tp.name().to_owned(), tp))
-            .collect::<HashMap<String, _>>();
-        let string_literals = IRModule::deserialize_array::<IRStringLiteral, D::Error>(
-            value.get("string_literals").ok_or(D::Error::custom("unable to deserialize IR module string literals"))?,
-            D::Error::custom("unable to deserialize IR module string literals"))?
-            .into_iter()
-            .map(| elem | (elem.identifier(), elem))
-            .collect::<HashMap<IRIdentifier, _>>();
-        let inline_assembly = IRModule::deserialize_array::<IRInlineAssembly, D::Error>(
-            value.get("inline_assembly").ok_or(D::Error::custom("unable to deserialize IR module inline assembly"))?,
-            D::Error::custom("unable to deserialize IR module inline assembly"))?
-            .into_iter()
-            .map(| elem | (elem.identifier(), elem))
-            .collect::<HashMap<IRIdentifier, _>>();
-        IRModule::new(globals, externals, types, string_literals, decls, funcs, data, inline_assembly)
-            .map_err(D::Error::custom)
+        IRModuleLoader::default().deserialize(deserializer)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IRModuleLoader {
+    max_type_depth: usize,
+    strict_arguments: bool,
+    strict_opcode_codes: bool,
+    require_sections: bool,
+    raw_numbers: bool
+}
+
+impl Default for IRModuleLoader {
+    fn default() -> IRModuleLoader {
+        IRModuleLoader {
+            max_type_depth: DEFAULT_MAX_TYPE_DEPTH,
+            strict_arguments: false,
+            strict_opcode_codes: false,
+            require_sections: false,
+            raw_numbers: false
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for IRModuleLoader {
+    type Value = IRModule;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IRModuleVisitor {
+            max_type_depth: self.max_type_depth,
+            strict_arguments: self.strict_arguments,
+            strict_opcode_codes: self.strict_opcode_codes,
+            require_sections: self.require_sections,
+            raw_numbers: self.raw_numbers
+        })
+    }
+}
+
+impl IRModuleLoader {
+    pub fn new() -> IRModuleLoader {
+        IRModuleLoader::default()
+    }
+
+    pub fn with_max_type_depth(mut self, max_type_depth: usize) -> IRModuleLoader {
+        self.max_type_depth = max_type_depth;
+        self
+    }
+
+    pub fn max_type_depth(&self) -> usize {
+        self.max_type_depth
+    }
+
+    pub fn with_strict_arguments(mut self, strict_arguments: bool) -> IRModuleLoader {
+        self.strict_arguments = strict_arguments;
+        self
+    }
+
+    pub fn strict_arguments(&self) -> bool {
+        self.strict_arguments
+    }
+
+    pub fn with_strict_opcode_codes(mut self, strict_opcode_codes: bool) -> IRModuleLoader {
+        self.strict_opcode_codes = strict_opcode_codes;
+        self
+    }
+
+    pub fn strict_opcode_codes(&self) -> bool {
+        self.strict_opcode_codes
+    }
+
+    pub fn with_required_sections(mut self, require_sections: bool) -> IRModuleLoader {
+        self.require_sections = require_sections;
+        self
+    }
+
+    pub fn required_sections(&self) -> bool {
+        self.require_sections
+    }
+
+    fn deserialize_json<R: io::Read>(self, reader: R) -> Result<IRModule, serde_json::Error> {
+        let reader = skip_byte_order_mark(reader)
+            .map_err(serde_json::Error::io)?;
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let loader = IRModuleLoader {
+            raw_numbers: true,
+            ..self
+        };
+        let module = loader.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(module)
+    }
+
+    pub fn from_json_reader<R: io::Read>(self, reader: R) -> Result<IRModule, LoadError> {
+        self.deserialize_json(reader)
+            .map_err(| err | LoadError::from_json(None, err))
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(self, path: P) -> Result<IRModule, LoadError> {
+        let path = path.as_ref();
+        let file = fs::File::open(path)
+            .map_err(| err | LoadError::IO(Some(path.to_owned()), err))?;
+        self.deserialize_json(io::BufReader::new(file))
+            .map_err(| err | LoadError::from_json(Some(path.to_owned()), err))
+    }
+
+    pub fn for_each_function<R, F, E>(self, reader: R, mut callback: F) -> Result<(), StreamError<E>>
+    where
+        R: io::Read,
+        F: FnMut(&IRModule, IRFunction) -> Result<(), E>
+    {
+        let mut callback_error = None;
+        let reader = skip_byte_order_mark(reader)
+            .map_err(| err | StreamError::Load(LoadError::IO(None, err)))?;
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let result = deserializer.deserialize_map(IRModuleStreamVisitor {
+            max_type_depth: self.max_type_depth,
+            strict_arguments: self.strict_arguments,
+            strict_opcode_codes: self.strict_opcode_codes,
+            callback: &mut callback,
+            callback_error: &mut callback_error
+        }).and_then(| _ | deserializer.end());
+        match (result, callback_error) {
+            (_, Some(err)) => Err(StreamError::Callback(err)),
+            (Err(err), None) => Err(StreamError::Load(LoadError::from_json(None, err))),
+            (Ok(()), None) => Ok(())
+        }
+    }
+
+    pub fn from_msgpack_slice(self, bytes: &[u8]) -> Result<IRModule, LoadError> {
+        self.deserialize(&mut rmp_serde::Deserializer::from_read_ref(bytes))
+            .map_err(LoadError::MessagePack)
+    }
+}
+
+impl IRModule {
+    pub fn from_json_reader<R: io::Read>(reader: R) -> Result<IRModule, LoadError> {
+        IRModuleLoader::default().from_json_reader(reader)
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<IRModule, LoadError> {
+        IRModuleLoader::default().from_json_file(path)
+    }
+
+    pub fn from_msgpack_slice(bytes: &[u8]) -> Result<IRModule, LoadError> {
+        IRModuleLoader::default().from_msgpack_slice(bytes)
+    }
+}
+
+pub fn for_each_function<R, F, E>(reader: R, callback: F) -> Result<(), StreamError<E>>
+where
+    R: io::Read,
+    F: FnMut(&IRModule, IRFunction) -> Result<(), E>
+{
+    IRModuleLoader::default().for_each_function(reader, callback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::okroshka::testing::{fixture_path, load_fixture};
+
+    #[test]
+    fn from_json_file_loads_fixture() {
+        let module = load_fixture("module.json");
+        assert!(module.get_function("main").is_some());
+        assert!(module.is_global("main"));
+        assert!(module.is_external("printf"));
+    }
+
+    #[test]
+    fn from_json_file_reports_missing_path() {
+        let path = fixture_path("missing.json");
+        match IRModule::from_json_file(&path) {
+            Err(err @ LoadError::IO(Some(_), _)) => {
+                assert_eq!(err.path(), Some(&path));
+                assert!(err.to_string().contains("missing.json"));
+            },
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn from_json_reader_reports_malformed_input() {
+        match IRModule::from_json_reader("{\"functions\": 1}".as_bytes()) {
+            Err(LoadError::Deserialize(None, _)) => (),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    fn module_with_body(body: &str) -> String {
+        r#"{"globals": [], "externals": [], "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "foo", "parameters": 0, "vararg": false, "returns": 0}],
+            "functions": [{"identifier": 0, "name": "foo", "locals": 0, "body": BODY}],
+            "data": [], "string_literals": [], "inline_assembly": []}"#.replace("BODY", body)
+    }
+
+    #[test]
+    fn deserialize_error_mentions_function_and_instruction() {
+        let json = module_with_body(r#"[{"opcode": "push", "arg": 1}, {"opcode": "push", "arg": "x"}, {"opcode": "ret"}]"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("in function `foo`, instruction 1:"), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_volatile_memflags() {
+        let flags = deserialize_instr_memflags::<serde_json::Error>(&serde_json::json!({
+            "opcode": "load8u", "memory_flags": {"volatile": true}
+        })).unwrap();
+        assert!(flags.volatile);
+        assert_eq!(flags.atomic, None);
+    }
+
+    #[test]
+    fn deserialize_volatile_seq_cst_memflags() {
+        let flags = deserialize_instr_memflags::<serde_json::Error>(&serde_json::json!({
+            "opcode": "load8u", "memory_flags": {"volatile": true, "atomic": "seq_cst"}
+        })).unwrap();
+        assert!(flags.volatile);
+        assert_eq!(flags.atomic, Some(IRMemoryOrder::SeqCst));
+    }
+
+    #[test]
+    fn deserialize_string_encoded_integers() {
+        let max = serde_json::json!({"opcode": "pushu", "arg": "18446744073709551615"});
+        assert_eq!(deserialize_instr_u64::<serde_json::Error>(&max).unwrap(), u64::MAX);
+        let min = serde_json::json!({"opcode": "push", "arg": "-9223372036854775808"});
+        assert_eq!(deserialize_instr_i64::<serde_json::Error>(&min).unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn deserialize_string_encoded_integer_overflow() {
+        let overflow = serde_json::json!({"opcode": "pushu", "arg": "18446744073709551616"});
+        let message = deserialize_instr_u64::<serde_json::Error>(&overflow).unwrap_err().to_string();
+        assert!(message.contains("out of u64 range"), "{}", message);
+    }
+
+    #[test]
+    fn streaming_load_matches_buffered_value() {
+        let json = std::fs::read_to_string(fixture_path("module.json")).unwrap();
+        let streamed = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let buffered = IRModule::deserialize(value).unwrap();
+        assert_eq!(streamed, buffered);
+    }
+
+    #[test]
+    fn streaming_element_errors_are_independent_of_field_order() {
+        let json = module_with_body("[]").replace(r#"{"identifier": 0, "name": "foo", "locals": 0, "body": []}"#,
+            r#"{"body": [{"opcode": "ret"}, {"opcode": "push", "arg": "x"}], "locals": 0, "name": "foo", "identifier": 0}"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("in function `foo`, instruction 1:"), "{}", message);
+
+        let json = module_with_body(r#"{"opcode": "ret"}"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("in function `foo`, unable to deserialize IR block"), "{}", message);
+
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#)
+            .replace(r#""data": []"#, r#""data": [{"value": 5, "type": 0, "storage": "global", "identifier": "d"}]"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("unable to deserialize IR data"), "{}", message);
+
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#)
+            .replace(r#"{"identifier": 0, "type": []}"#, r#"{"type": [{"type": "quantum"}], "identifier": 0}"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("unable to deserialize IR type entry"), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_unknown_opcode_is_an_error() {
+        let json = module_with_body(r#"[{"opcode": "frobnicate"}]"#);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("unknown opcode frobnicate"), "{}", message);
+    }
+
+    #[test]
+    fn msgpack_round_trip_matches_json() {
+        let json = std::fs::read_to_string(fixture_path("module.json")).unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let bytes = rmp_serde::to_vec(&value).unwrap();
+        assert_eq!(IRModule::from_msgpack_slice(&bytes).unwrap(), load_fixture("module.json"));
+    }
+
+    fn module_with_revision(revision: u64) -> String {
+        module_with_body(r#"[{"opcode": "ret"}]"#).replacen('{', &format!("{{\"opcode_revision\": {}, ", revision), 1)
+    }
+
+    #[test]
+    fn opcode_revision_validation() {
+        let supported = IRInstruction::revision().unwrap_or(1);
+        assert!(IRModule::from_json_reader(module_with_revision(supported).as_bytes()).is_ok());
+        assert!(IRModule::from_json_reader(module_with_body(r#"[{"opcode": "ret"}]"#).as_bytes()).is_ok());
+        if IRInstruction::revision().is_some() {
+            let message = IRModule::from_json_reader(module_with_revision(supported + 1).as_bytes())
+                .unwrap_err()
+                .to_string();
+            assert!(message.contains("does not match supported revision"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn opcode_revision_is_exposed() {
+        let revision = IRInstruction::revision().unwrap_or(5);
+        let module = IRModule::from_json_reader(module_with_revision(revision).as_bytes()).unwrap();
+        assert_eq!(module.opcode_revision(), Some(revision));
+        assert_eq!(load_fixture("module.json").opcode_revision(), None);
+    }
+
+    #[test]
+    fn deserialize_builtin_type_classes() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#"{"identifier": 0, "type": []}"#,
+            r#"{"identifier": 0, "type": []}, {"identifier": 1, "type": [{"type": "builtin", "class": "vararg"}, {"type": "builtin", "class": "future"}]}"#, 1);
+        let module = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        let entries = module.get_type(1).unwrap().type_entries().collect::<Vec<_>>();
+        assert_eq!(entries, vec![
+            &IRTypeEntry::Builtin { alignment: None, builtin: IRTypeBuiltin::VarargList },
+            &IRTypeEntry::Builtin { alignment: None, builtin: IRTypeBuiltin::Unknown("future".to_owned()) }
+        ]);
+    }
+
+    #[test]
+    fn deserialize_unknown_data_class_is_preserved() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""data": []"#,
+            r#""data": [{"identifier": "blob", "storage": "global", "type": 0, "value": [{"class": "quantum", "qubits": 3}]}]"#, 1);
+        let module = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        let element = module.get_data("blob").unwrap().elements().next();
+        match element {
+            Some(IRDataElement::Unknown { class, value }) => {
+                assert_eq!(class, "quantum");
+                assert_eq!(value["qubits"], 3);
+            },
+            other => panic!("unexpected element {:?}", other)
+        }
+    }
+
+    #[test]
+    fn deserialize_long_double_preserves_representation() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""data": []"#,
+            r#""data": [{"identifier": "pi", "storage": "global", "type": 0, "value": [{"class": "long_double", "value": 3.14159265358979323846264338327950288}]}]"#, 1);
+        let module = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        match module.get_data("pi").unwrap().elements().next() {
+            Some(IRDataElement::LongDouble(value)) => {
+                assert_eq!(value.as_str(), "3.14159265358979323846264338327950288");
+                assert_eq!(value.to_f64(), std::f64::consts::PI);
+            },
+            other => panic!("unexpected element {:?}", other)
+        }
+        let json = json.replacen("3.14159265358979323846264338327950288", r#""pi""#, 1);
+        assert!(IRModule::from_json_reader(json.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn deserialize_rejects_out_of_range_raw_bytes() {
+        let element = serde_json::json!({"class": "raw", "value": [0, 255, 256]});
+        let message = serde_json::from_value::<IRDataElement>(element).unwrap_err().to_string();
+        assert!(message.contains("raw data byte 256 is out of u8 range"), "{}", message);
+        let element = serde_json::json!({"class": "raw", "value": [0, 255]});
+        assert_eq!(serde_json::from_value::<IRDataElement>(element).unwrap(), IRDataElement::Raw(vec![0, 255]));
+    }
+
+    #[test]
+    fn deserialize_rejects_out_of_range_code_units() {
+        let literal = serde_json::json!({"id": 0, "public": false, "type": "unicode16", "literal": [65, 70000, 0]});
+        let message = serde_json::from_value::<IRStringLiteral>(literal).unwrap_err().to_string();
+        assert!(message.contains("code unit 70000 is out of u16 range"), "{}", message);
+        let literal = serde_json::json!({"id": 0, "public": false, "type": "unicode16", "literal": [65, 65535, 0]});
+        assert_eq!(serde_json::from_value::<IRStringLiteral>(literal).unwrap().content(),
+            &IRStringLiteralContent::Unicode16(vec![65, 65535, 0]));
+    }
+
+    fn nested_array_type(depth: usize) -> String {
+        let mut entry = r#"{"type": "int"}"#.to_owned();
+        for _ in 0..depth {
+            entry = format!(r#"{{"type": "array", "length": 1, "element_type": {}}}"#, entry);
+        }
+        module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#"{"identifier": 0, "type": []}"#,
+            &format!(r#"{{"identifier": 0, "type": []}}, {{"identifier": 1, "type": [{}]}}"#, entry), 1)
+    }
+
+    #[test]
+    fn deserialize_rejects_types_nested_past_limit() {
+        let loader = IRModuleLoader::new().with_max_type_depth(16);
+        assert!(loader.from_json_reader(nested_array_type(15).as_bytes()).is_ok());
+        let message = loader.from_json_reader(nested_array_type(64).as_bytes())
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("depth"), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_hostile_instruction_is_an_error() {
+        for value in [serde_json::json!({"opcode": "frobnicate"}), serde_json::json!({"opcode": 7}), serde_json::json!([])] {
+            assert!(IRInstruction::deserialize_value::<serde_json::Error>(&value, true, true).is_err());
+        }
+    }
+
+    #[test]
+    fn deserialize_missing_section_as_empty() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#", "inline_assembly": []"#, "", 1);
+        let module = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        assert_eq!(module.inline_assembly().count(), 0);
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""inline_assembly": []"#, r#""inline_assembly": {}"#, 1);
+        assert!(IRModule::from_json_reader(json.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn deserialize_function_declaration_names() {
+        let named = | name: &str | serde_json::from_str::<IRFunctionDeclaration>(&format!(
+            r#"{{"identifier": 0, {}"parameters": 0, "vararg": false, "returns": 0}}"#, name));
+        assert_eq!(named(r#""name": null, "#).unwrap().name(), None);
+        assert_eq!(named("").unwrap().name(), None);
+        assert_eq!(named(r#""name": "f", "#).unwrap().name(), Some("f"));
+        assert!(named(r#""name": 5, "#).is_err());
+    }
+
+    #[test]
+    fn deserialize_hidden_weak_symbol() {
+        let symbol = serde_json::from_str::<IRSymbol>(
+            r#"{"identifier": "x", "type": "thread_local", "visibility": "hidden", "weak": true}"#).unwrap();
+        assert_eq!(symbol.name(), "x");
+        assert!(symbol.is_thread_local());
+        assert_eq!(symbol.attributes(), IRSymbolAttributes { visibility: IRSymbolVisibility::Hidden, weak: true });
+        let symbol = serde_json::from_str::<IRSymbol>(r#"{"identifier": "y", "type": "global"}"#).unwrap();
+        assert_eq!(symbol.attributes(), IRSymbolAttributes::default());
+        assert!(serde_json::from_str::<IRSymbol>(r#"{"identifier": "z", "type": "global", "visibility": "internal"}"#).is_err());
+        assert!(serde_json::from_str::<IRSymbol>(r#"{"identifier": "z", "type": "global", "weak": 1}"#).is_err());
+    }
+
+    #[test]
+    fn disassemble_hidden_weak_symbol() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""globals": []"#,
+            r#""globals": [{"identifier": "foo", "type": "global", "visibility": "hidden", "weak": true}]"#, 1);
+        let module = IRModule::from_json_reader(json.as_bytes()).unwrap();
+        let mut output = String::new();
+        module.disassemble(&mut output).unwrap();
+        assert!(output.contains("global weak hidden foo\n"), "{}", output);
+    }
+
+    #[test]
+    fn deserialize_rejects_duplicate_type_id() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""types": [{"identifier": 0, "type": []}]"#,
+            r#""types": [{"identifier": 0, "type": []}, {"identifier": 0, "type": [{"type": "int"}]}]"#, 1);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("Detected duplicating IR type identifier 0"), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_rejects_duplicate_function_name() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""functions": ["#,
+            r#""functions": [{"identifier": 0, "name": "foo", "locals": 0, "body": []}, "#, 1);
+        let message = IRModule::from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains("Detected duplicating IR function foo"), "{}", message);
+    }
+
+    #[test]
+    fn strict_arguments_reject_spurious_argument() {
+        let json = module_with_body(r#"[{"opcode": "push", "arg": 1}, {"opcode": "iadd", "arg": 2}, {"opcode": "ret"}]"#);
+        assert!(IRModuleLoader::new().from_json_reader(json.as_bytes()).is_ok());
+        let message = IRModuleLoader::new()
+            .with_strict_arguments(true)
+            .from_json_reader(json.as_bytes())
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("instruction 1: IR instruction iadd does not take an argument"), "{}", message);
+    }
+
+    #[test]
+    fn strict_opcode_codes_reject_mismatched_code() {
+        let loader = IRModuleLoader::new().with_strict_opcode_codes(true);
+        let code = IRInstruction::ret.code();
+        let json = module_with_body(&format!(r#"[{{"opcode": "ret", "code": {}}}]"#, code));
+        assert!(loader.from_json_reader(json.as_bytes()).is_ok());
+        let json = module_with_body(&format!(r#"[{{"opcode": "ret", "code": {}}}]"#, code + 1));
+        assert!(IRModule::from_json_reader(json.as_bytes()).is_ok());
+        let message = loader.from_json_reader(json.as_bytes()).unwrap_err().to_string();
+        assert!(message.contains(&format!("IR instruction ret opcode code {} does not match expected code {}", code + 1, code)), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_distinguishes_missing_and_malformed_sections() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#);
+        let missing = json.replacen(r#""data": [], "#, "", 1);
+        let message = IRModuleLoader::new()
+            .with_required_sections(true)
+            .from_json_reader(missing.as_bytes())
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("missing field `data`"), "{}", message);
+        let malformed = json.replacen(r#""data": []"#, r#""data": 5"#, 1);
+        let message = IRModuleLoader::new()
+            .with_required_sections(true)
+            .from_json_reader(malformed.as_bytes())
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("expected an array of IR module data"), "{}", message);
+    }
+
+    #[test]
+    fn deserialize_symbol_forms() {
+        let symbol = serde_json::from_str::<IRSymbol>(r#"{"identifier": "foo", "type": "global"}"#).unwrap();
+        assert_eq!(symbol, IRSymbol::Global("foo".into(), IRSymbolAttributes::default()));
+        let symbol = serde_json::from_str::<IRSymbol>(r#""foo""#).unwrap();
+        assert_eq!(symbol, IRSymbol::Global("foo".into(), IRSymbolAttributes::default()));
+        assert!(serde_json::from_str::<IRSymbol>("5").is_err());
+    }
+
+    fn inline_asm_with_parameter(param: &str) -> Result<IRInlineAssembly, serde_json::Error> {
+        serde_json::from_str(&format!(r#"{{"identifier": 0, "global": false, "template": "nop", "parameters": [
+            {{"identifier": 3, "names": ["x"], "constraint": "register", {}}}
+        ], "clobbers": [], "jump_targets": []}}"#, param))
+    }
+
+    #[test]
+    fn deserialize_inline_asm_parameter_classes() {
+        let class_of = | param: &str | inline_asm_with_parameter(param).unwrap().get_parameter(3).unwrap().klass().clone();
+        assert_eq!(class_of(r#""class": "read_store", "from_type": 1, "from_type_index": 2, "from": 3, "type": 4, "type_index": 5, "to": 6"#),
+            IRInlineAssemblyParameterClass::ReadStore(IRTypeRef::new(1, 2), 3, IRTypeRef::new(4, 5), 6));
+        assert_eq!(class_of(r#""class": "immediate", "type": 1, "type_index": 0, "variant": "identifier_based", "base": "sym", "value": 8"#),
+            IRInlineAssemblyParameterClass::ImmediateIdentifierBased(IRTypeRef::new(1, 0), "sym".to_owned(), 8));
+        assert_eq!(class_of(r#""class": "immediate", "type": 1, "type_index": 0, "variant": "identifier_based", "base": null, "value": 8"#),
+            IRInlineAssemblyParameterClass::ImmediateConstant(IRTypeRef::new(1, 0), 8));
+        assert_eq!(class_of(r#""class": "immediate", "type": 1, "type_index": 0, "variant": "literal_based", "base": 2, "value": 8"#),
+            IRInlineAssemblyParameterClass::ImmediateLiteralBased(IRTypeRef::new(1, 0), 2, 8));
+    }
+
+    #[test]
+    fn deserialize_inline_asm_parameter_errors_name_field() {
+        let cases = [
+            (r#""class": "read", "type": 1, "from": 0"#, "type_index"),
+            (r#""class": "read_store", "from_type": 1, "from_type_index": 2, "from": 3, "type": 4, "type_index": 5"#, "to"),
+            (r#""class": "immediate", "type": 1, "type_index": 0, "value": 8"#, "variant"),
+            (r#""class": "immediate", "type": 1, "type_index": 0, "variant": "literal_based", "value": 8"#, "base"),
+            (r#""class": "teleport""#, "class")
+        ];
+        for (param, field) in cases {
+            let message = inline_asm_with_parameter(param).unwrap_err().to_string();
+            assert!(message.contains(&format!("parameter 3 field `{}`", field)), "{}", message);
+        }
+    }
+
+    #[test]
+    fn for_each_function_streams_functions() {
+        let json = module_with_body(r#"[{"opcode": "ret"}]"#).replacen(r#""functions": ["#,
+            r#""functions": [{"identifier": 0, "name": "bar", "locals": 0, "body": []}, "#, 1);
+        let mut names = Vec::new();
+        for_each_function(json.as_bytes(), | _, func | -> Result<(), ()> {
+            names.push(func.name().to_owned());
+            Ok(())
+        }).unwrap();
+        assert_eq!(names, vec!["bar", "foo"]);
+
+        let mut decl_names = Vec::new();
+        for_each_function(json.as_bytes(), | context, func | -> Result<(), ()> {
+            decl_names.push(context.get_function_declaration(func.declaration_id()).and_then(| decl | decl.name()).map(str::to_owned));
+            Ok(())
+        }).unwrap();
+        assert_eq!(decl_names, vec![Some("foo".to_owned()), Some("foo".to_owned())]);
+
+        let mut count = 0;
+        match for_each_function(json.as_bytes(), | _, _ | {
+            count += 1;
+            Err("stop")
+        }) {
+            Err(StreamError::Callback("stop")) => assert_eq!(count, 1),
+            other => panic!("unexpected result {:?}", other)
+        }
+    }
+
+    #[test]
+    fn for_each_function_validates_against_types_and_declarations() {
+        let calls_unknown = module_with_body(r#"[{"opcode": "invoke", "arg": {"identifier": 5, "name": "bar"}}, {"opcode": "ret"}]"#);
+        let unknown_local = module_with_body(r#"[{"opcode": "getlocal", "arg": {"type": 0, "index": 0}}, {"opcode": "ret"}]"#);
+        let unknown_decl = module_with_body(r#"[{"opcode": "ret"}]"#).replace(r#""identifier": 0, "name": "foo", "locals""#,
+            r#""identifier": 3, "name": "foo", "locals""#);
+        for json in [calls_unknown, unknown_local, unknown_decl] {
+            let mut count = 0;
+            match for_each_function(json.as_bytes(), | _, _ | -> Result<(), ()> {
+                count += 1;
+                Ok(())
+            }) {
+                Err(StreamError::Load(_)) => assert_eq!(count, 0),
+                other => panic!("unexpected result {:?}", other)
+            }
+        }
+    }
+
+    #[test]
+    fn for_each_function_validates_functions_preceding_types_and_declarations() {
+        let json = r#"{"functions": [{"identifier": 0, "name": "foo", "locals": 0, "body": [{"opcode": "ret"}]}],
+            "types": [{"identifier": 0, "type": []}],
+            "function_declarations": [{"identifier": 0, "name": "foo", "parameters": 0, "vararg": false, "returns": 0}]}"#;
+        let mut names = Vec::new();
+        for_each_function(json.as_bytes(), | context, func | -> Result<(), ()> {
+            assert!(context.get_function_declaration(func.declaration_id()).is_some());
+            names.push(func.name().to_owned());
+            Ok(())
+        }).unwrap();
+        assert_eq!(names, vec!["foo"]);
+
+        let json = json.replace(r#""identifier": 0, "name": "foo", "parameters""#, r#""identifier": 1, "name": "foo", "parameters""#);
+        assert!(matches!(for_each_function(json.as_bytes(), | _, _ | -> Result<(), ()> { Ok(()) }), Err(StreamError::Load(_))));
+    }
+
+    #[test]
+    fn byte_order_mark_is_skipped() {
+        let mut json = UTF8_BYTE_ORDER_MARK.to_vec();
+        json.extend_from_slice(module_with_body(r#"[{"opcode": "ret"}]"#).as_bytes());
+        let module = IRModule::from_json_reader(json.as_slice()).unwrap();
+        assert_eq!(module, IRModule::from_json_reader(&json[UTF8_BYTE_ORDER_MARK.len()..]).unwrap());
+
+        let mut names = Vec::new();
+        for_each_function(json.as_slice(), | _, func | -> Result<(), ()> {
+            names.push(func.name().to_owned());
+            Ok(())
+        }).unwrap();
+        assert_eq!(names, vec!["foo"]);
+
+        assert!(IRModule::from_json_reader(&json[1..]).is_err());
+        assert!(IRModule::from_json_reader(&b"{}"[..]).is_ok());
     }
 }
@@ -1,3 +1,8 @@
+pub mod error;
 pub mod ir;
+pub mod binary;
+pub mod diagnostic;
 
+pub use crate::okroshka::loader::error::*;
 pub use crate::okroshka::loader::ir::*;
+pub use crate::okroshka::loader::diagnostic::*;
@@ -1,2 +1,5 @@
 pub mod ir;
-pub mod loader;
\ No newline at end of file
+pub mod loader;
+
+#[cfg(test)]
+pub(crate) mod testing;
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+use crate::okroshka::ir::IRModule;
+
+pub(crate) fn fixture_path(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name].iter().collect()
+}
+
+pub(crate) fn load_fixture(name: &str) -> IRModule {
+    IRModule::from_json_file(fixture_path(name)).unwrap()
+}
+
+pub(crate) fn load_module(json: &str) -> IRModule {
+    IRModule::from_json_reader(json.as_bytes()).unwrap()
+}